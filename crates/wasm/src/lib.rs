@@ -1,7 +1,122 @@
 use std::cell::RefCell;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(all(feature = "parallel", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+/// Failure categories reported by fallible exports.
+///
+/// Errors reach JS as a thrown `Error` whose message starts with the variant
+/// name, e.g. `"BufferTooSmall: box_blur: data has 12 bytes, expected 16"`, so
+/// `ErrorCode[message.split(':')[0]]` recovers the numeric code.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    NullPointer = 1,
+    BufferTooSmall = 2,
+    LengthMismatch = 3,
+    InvalidDimensions = 4,
+    SizeOverflow = 5,
+    InvalidArgument = 6,
+    /// Encoded input (e.g. a WAV file) is malformed or unsupported.
+    InvalidFormat = 7,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasmError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl WasmError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl std::fmt::Display for WasmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for WasmError {}
+
+fn require_non_null<T>(ptr: *const T, len: usize, what: &str) -> Result<(), WasmError> {
+    if ptr.is_null() && len > 0 {
+        return Err(WasmError::new(
+            ErrorCode::NullPointer,
+            format!("{what} is null but {len} elements were requested"),
+        ));
+    }
+    Ok(())
+}
+
+fn require_len(actual: usize, expected: usize, what: &str) -> Result<(), WasmError> {
+    if actual < expected {
+        return Err(WasmError::new(
+            ErrorCode::BufferTooSmall,
+            format!("{what} has {actual} elements, expected at least {expected}"),
+        ));
+    }
+    Ok(())
+}
+
+fn require_finite(value: f64, what: &str) -> Result<(), WasmError> {
+    if !value.is_finite() {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what} must be finite, got {value}"),
+        ));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// THREAD POOL
+// ============================================================================
+
+/// Inputs shorter than this stay on the calling thread; splitting them costs
+/// more in scheduling than it saves.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_LEN: usize = 1 << 16;
+
+/// Number of elements each task reduces on its own before results are combined.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_LEN: usize = 1 << 14;
+
+/// Number of worker threads kernels can split across.
+/// Always 1 when the crate is built without the `parallel` feature.
+#[wasm_bindgen]
+#[must_use]
+pub fn thread_count() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
 // ============================================================================
 // FIBONACCI FUNCTIONS
 // ============================================================================
@@ -98,6 +213,128 @@ pub fn free_u32(ptr: *mut u32, len: usize) {
     }
 }
 
+// ============================================================================
+// CANCELLATION & PROGRESS
+// ============================================================================
+
+/// Outcome of a cancellable kernel.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelStatus {
+    Completed = 0,
+    Cancelled = 1,
+}
+
+/// Control block shared between a long-running kernel and its caller.
+///
+/// Layout is three `i32` words: `[cancel, progress, total]`. The caller stores a
+/// non-zero value in `cancel` to request a stop; the kernel writes `total` when it
+/// starts and increments `progress` towards it. From JS, view it with
+/// `new Int32Array(memory.buffer, ptr, 3)` and use `Atomics` on the shared build.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct KernelControl {
+    cancel: AtomicI32,
+    progress: AtomicI32,
+    total: AtomicI32,
+}
+
+impl KernelControl {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(1, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) != 0
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> i32 {
+        self.progress.load(Ordering::Relaxed)
+    }
+
+    #[must_use]
+    pub fn total(&self) -> i32 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    fn begin(&self, total: usize) {
+        self.progress.store(0, Ordering::Relaxed);
+        self.total
+            .store(i32::try_from(total).unwrap_or(i32::MAX), Ordering::Relaxed);
+    }
+
+    fn advance(&self, units: usize) {
+        let units = i32::try_from(units).unwrap_or(i32::MAX);
+        self.progress.fetch_add(units, Ordering::Relaxed);
+    }
+}
+
+/// Marker error for a kernel that stopped because its control block was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cancelled;
+
+/// Publishes `units` of finished work and reports whether the kernel should stop.
+fn checkpoint(control: Option<&KernelControl>, units: usize) -> Result<(), Cancelled> {
+    let Some(control) = control else {
+        return Ok(());
+    };
+    control.advance(units);
+    if control.is_cancelled() {
+        return Err(Cancelled);
+    }
+    Ok(())
+}
+
+fn kernel_status(result: Result<(), Cancelled>) -> KernelStatus {
+    match result {
+        Ok(()) => KernelStatus::Completed,
+        Err(Cancelled) => KernelStatus::Cancelled,
+    }
+}
+
+/// Allocates a zeroed `KernelControl` block (three `i32` words) in wasm memory.
+#[wasm_bindgen]
+#[must_use]
+pub fn alloc_control() -> *mut i32 {
+    Box::into_raw(Box::new(KernelControl::new())).cast::<i32>()
+}
+
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn free_control(ptr: *mut i32) {
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: `ptr` must come from `alloc_control` and must not be used afterwards.
+    unsafe {
+        drop(Box::from_raw(ptr.cast::<KernelControl>()));
+    }
+}
+
+/// Borrows the control block behind a pointer from JS; null means "not cancellable".
+fn control_from_ptr<'a>(ptr: *const i32) -> Result<Option<&'a KernelControl>, WasmError> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    if !ptr.cast::<KernelControl>().is_aligned() {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            "control block pointer is not 4-byte aligned",
+        ));
+    }
+    // SAFETY: Caller guarantees `ptr` points to three live `i32` words (as returned by
+    // `alloc_control`) for the duration of the kernel. `KernelControl` is `repr(C)`
+    // over atomics with the same size and alignment as `i32`.
+    Ok(Some(unsafe { &*ptr.cast::<KernelControl>() }))
+}
+
 // ============================================================================
 // ARRAY OPERATIONS
 // ============================================================================
@@ -109,28 +346,43 @@ pub fn process_shared_buffer(arr: &mut [u32]) {
     }
 }
 
+/// Pointer variant of `process_shared_buffer` for a `u32` buffer already in wasm
+/// memory.
+///
+/// # Errors
+///
+/// `NullPointer` if `ptr` is null and `len` is non-zero.
 #[wasm_bindgen]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn process_shared_buffer_ptr(ptr: *mut u32, len: usize) {
-    if ptr.is_null() || len == 0 {
-        return;
+pub fn process_shared_buffer_ptr(ptr: *mut u32, len: usize) -> Result<(), JsError> {
+    require_non_null(ptr, len, "process_shared_buffer_ptr: ptr")?;
+    if len == 0 {
+        return Ok(());
     }
     // SAFETY: Caller guarantees `ptr` points to `len` initialized `u32` elements
     // that are writable for the duration of this call.
     let arr = unsafe { std::slice::from_raw_parts_mut(ptr, len) };
     process_shared_buffer(arr);
+    Ok(())
 }
 
 #[wasm_bindgen]
 #[must_use]
 pub fn sum_u32(arr: &[u32]) -> u32 {
+    #[cfg(feature = "parallel")]
+    if arr.len() >= PARALLEL_MIN_LEN {
+        return arr
+            .par_chunks(PARALLEL_CHUNK_LEN)
+            .map(|chunk| chunk.iter().copied().sum::<u32>())
+            .sum();
+    }
     arr.iter().copied().sum()
 }
 
 #[wasm_bindgen]
 #[must_use]
 pub fn sum_u32_sab(arr: &[u32]) -> u32 {
-    arr.iter().copied().sum()
+    sum_u32(arr)
 }
 
 /// SIMD-style sum for f32 arrays using loop unrolling
@@ -138,6 +390,14 @@ pub fn sum_u32_sab(arr: &[u32]) -> u32 {
 #[wasm_bindgen]
 #[must_use]
 pub fn sum_f32_simd(arr: &[f32]) -> f32 {
+    #[cfg(feature = "parallel")]
+    if arr.len() >= PARALLEL_MIN_LEN {
+        return arr.par_chunks(PARALLEL_CHUNK_LEN).map(sum_f32_serial).sum();
+    }
+    sum_f32_serial(arr)
+}
+
+fn sum_f32_serial(arr: &[f32]) -> f32 {
     #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
     // SAFETY: The SIMD helper only reads within slice bounds and handles remainder safely.
     unsafe {
@@ -197,9 +457,35 @@ unsafe fn sum_f32_simd128(arr: &[f32]) -> f32 {
 
 /// SIMD-style dot product using loop unrolling
 /// 4-way parallel accumulation for better CPU pipeline utilization
+///
+/// # Errors
+///
+/// `LengthMismatch` if `a` and `b` differ in length.
 #[wasm_bindgen]
-#[must_use]
-pub fn dot_product_simd(a: &[f32], b: &[f32]) -> f32 {
+pub fn dot_product_simd(a: &[f32], b: &[f32]) -> Result<f32, JsError> {
+    if a.len() != b.len() {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!(
+                "dot_product_simd: a has {} elements but b has {}",
+                a.len(),
+                b.len()
+            ),
+        )
+        .into());
+    }
+    #[cfg(feature = "parallel")]
+    if a.len() >= PARALLEL_MIN_LEN {
+        return Ok(a
+            .par_chunks(PARALLEL_CHUNK_LEN)
+            .zip(b.par_chunks(PARALLEL_CHUNK_LEN))
+            .map(|(ca, cb)| dot_product_serial(ca, cb))
+            .sum());
+    }
+    Ok(dot_product_serial(a, b))
+}
+
+fn dot_product_serial(a: &[f32], b: &[f32]) -> f32 {
     #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
     // SAFETY: The SIMD helper clamps to the minimum input length and handles remainder safely.
     unsafe {
@@ -274,15 +560,31 @@ unsafe fn dot_product_simd128(a: &[f32], b: &[f32]) -> f32 {
 // IMAGE PROCESSING
 // ============================================================================
 
+/// Converts RGBA pixels to grayscale in place, leaving alpha unchanged.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `data.len()` is not a multiple of 4.
 #[wasm_bindgen]
-pub fn grayscale(data: &mut [u8]) {
+pub fn grayscale(data: &mut [u8]) -> Result<(), JsError> {
+    if !data.len().is_multiple_of(4) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!(
+                "grayscale: data has {} bytes, not a whole number of RGBA pixels",
+                data.len()
+            ),
+        )
+        .into());
+    }
     #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
     // SAFETY: SIMD path only mutates bytes inside `data` and falls back for tail elements.
     unsafe {
         grayscale_simd128(data);
-        return;
+        return Ok(());
     }
     grayscale_scalar(data);
+    Ok(())
 }
 
 fn grayscale_scalar(data: &mut [u8]) {
@@ -296,6 +598,17 @@ fn grayscale_scalar(data: &mut [u8]) {
     }
 }
 
+/// Luminance of each RGBA pixel with the weights `grayscale` uses, so a
+/// `grayscale` output maps back to its grey level exactly.
+fn luminance_plane(pixels: &[u8]) -> Vec<f64> {
+    pixels
+        .chunks_exact(4)
+        .map(|p| {
+            (77.0 * f64::from(p[0]) + 150.0 * f64::from(p[1]) + 29.0 * f64::from(p[2])) / 256.0
+        })
+        .collect()
+}
+
 #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[target_feature(enable = "simd128")]
 unsafe fn grayscale_simd128(data: &mut [u8]) {
@@ -332,110 +645,6363 @@ unsafe fn grayscale_simd128(data: &mut [u8]) {
         i += 4;
     }
 
-    let tail_start = i * 4;
-    if tail_start < data.len() {
-        grayscale_scalar(&mut data[tail_start..]);
-    }
+    let tail_start = i * 4;
+    if tail_start < data.len() {
+        grayscale_scalar(&mut data[tail_start..]);
+    }
+}
+
+thread_local! {
+    static BLUR_SCRATCH: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Averages each colour channel over the `(2r + 1)^2` neighbourhood clipped to
+/// the image (alpha is kept). Separable running sums make the cost independent
+/// of `radius`; one channel of row sums is kept in a reused scratch buffer.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if the image size
+/// overflows `usize`, and `BufferTooSmall` if `data` holds fewer than
+/// `width * height * 4` bytes.
+#[wasm_bindgen]
+pub fn box_blur(data: &mut [u8], width: u32, height: u32, radius: u32) -> Result<(), JsError> {
+    let w = usize::try_from(width).unwrap_or(usize::MAX);
+    let h = usize::try_from(height).unwrap_or(usize::MAX);
+    let r = usize::try_from(radius).unwrap_or(usize::MAX);
+    let pixel_bytes = rgba_len(w, h, "box_blur")?;
+    require_len(data.len(), pixel_bytes, "box_blur: data")?;
+
+    let pixels = &mut data[..pixel_bytes];
+    let column_counts: Vec<u64> = (0..w).map(|x| clipped_window(x, r, w)).collect();
+    BLUR_SCRATCH.with(|cell| {
+        let mut sums = cell.borrow_mut();
+        sums.clear();
+        sums.resize(w * h, 0);
+        for channel in 0..3 {
+            box_blur_channel(pixels, &mut sums, &column_counts, h, r, channel);
+        }
+    });
+    Ok(())
+}
+
+/// Pixels covered by a radius-`r` window around `i` once clipped to `0..n`.
+fn clipped_window(i: usize, r: usize, n: usize) -> u64 {
+    let covered = i.saturating_add(r).min(n - 1) - i.saturating_sub(r) + 1;
+    u64::try_from(covered).unwrap_or(u64::MAX)
+}
+
+/// Blurs one channel: clipped horizontal window sums go to `sums`, then a
+/// vertical running sum over them yields the integer mean of each clipped box,
+/// exactly as summing the box directly would.
+fn box_blur_channel(
+    pixels: &mut [u8],
+    sums: &mut [u32],
+    column_counts: &[u64],
+    h: usize,
+    r: usize,
+    channel: usize,
+) {
+    let w = column_counts.len();
+
+    #[cfg(feature = "parallel")]
+    if pixels.len() >= PARALLEL_MIN_LEN {
+        sums.par_chunks_mut(w)
+            .zip(pixels.par_chunks(w * 4))
+            .for_each(|(row_sums, row)| box_sums_row(row, row_sums, r, channel));
+        let block_rows = h.div_ceil(rayon::current_num_threads());
+        pixels
+            .par_chunks_mut(block_rows * w * 4)
+            .enumerate()
+            .for_each(|(block, rows)| {
+                box_blur_rows(rows, sums, column_counts, h, r, channel, block * block_rows);
+            });
+        return;
+    }
+
+    for (row_sums, row) in sums.chunks_exact_mut(w).zip(pixels.chunks_exact(w * 4)) {
+        box_sums_row(row, row_sums, r, channel);
+    }
+    box_blur_rows(pixels, sums, column_counts, h, r, channel, 0);
+}
+
+/// Running sums of one channel of an RGBA row over radius-`r` clipped windows.
+fn box_sums_row(row: &[u8], sums: &mut [u32], r: usize, channel: usize) {
+    let w = sums.len();
+    let value = |x: usize| u32::from(row[x * 4 + channel]);
+    let mut sum: u32 = (0..=r.min(w - 1)).map(value).sum();
+    for (x, slot) in sums.iter_mut().enumerate() {
+        *slot = sum;
+        if r < w - 1 - x {
+            sum += value(x + r + 1);
+        }
+        if x >= r {
+            sum -= value(x - r);
+        }
+    }
+}
+
+/// Writes the blurred `channel` of the output rows starting at image row
+/// `first_row`, sliding a vertical window over the horizontal sums.
+fn box_blur_rows(
+    rows: &mut [u8],
+    sums: &[u32],
+    column_counts: &[u64],
+    h: usize,
+    r: usize,
+    channel: usize,
+    first_row: usize,
+) {
+    let w = column_counts.len();
+    let add_row = |totals: &mut [u64], y: usize, add: bool| {
+        for (total, &sum) in totals.iter_mut().zip(&sums[y * w..(y + 1) * w]) {
+            if add {
+                *total += u64::from(sum);
+            } else {
+                *total -= u64::from(sum);
+            }
+        }
+    };
+
+    let mut totals = vec![0u64; w];
+    let window_end = first_row.saturating_add(r).min(h - 1);
+    for y in first_row.saturating_sub(r)..=window_end {
+        add_row(&mut totals, y, true);
+    }
+    for (y, row) in (first_row..).zip(rows.chunks_exact_mut(w * 4)) {
+        let row_count = clipped_window(y, r, h);
+        for ((pixel, &total), &count) in row.chunks_exact_mut(4).zip(&totals).zip(column_counts) {
+            pixel[channel] = u8::try_from(total / (row_count * count)).unwrap_or(u8::MAX);
+        }
+        if r < h - 1 - y {
+            add_row(&mut totals, y + r + 1, true);
+        }
+        if y >= r {
+            add_row(&mut totals, y - r, false);
+        }
+    }
+}
+
+/// Byte length of a `w` x `h` RGBA image, rejecting empty or overflowing sizes.
+fn rgba_len(w: usize, h: usize, what: &str) -> Result<usize, WasmError> {
+    if w == 0 || h == 0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: image is {w}x{h}, both sides must be non-zero"),
+        ));
+    }
+    w.checked_mul(h)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or_else(|| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                format!("{what}: {w}x{h} RGBA image does not fit in memory"),
+            )
+        })
+}
+
+/// Width, height and RGBA byte length of an image passed from JS.
+fn image_dims(width: u32, height: u32, what: &str) -> Result<(usize, usize, usize), WasmError> {
+    let w = usize::try_from(width).unwrap_or(usize::MAX);
+    let h = usize::try_from(height).unwrap_or(usize::MAX);
+    Ok((w, h, rgba_len(w, h, what)?))
+}
+
+/// Rounds and clamps to `0..=255`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn clamp_to_u8(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// How `gaussian_blur` samples pixels beyond the image border.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeMode {
+    /// Repeat the outermost pixel.
+    Clamp = 0,
+    /// Reflect about the outermost pixel without repeating it (`c b | a b c`).
+    Mirror = 1,
+    /// Tile the image periodically.
+    Wrap = 2,
+    /// Treat everything outside as zero (black).
+    Constant = 3,
+}
+
+impl EdgeMode {
+    /// Source index for position `i` of a line of `n` samples, `None` for a zero.
+    fn source_index(self, i: isize, n: usize) -> Option<usize> {
+        let n = isize::try_from(n).unwrap_or(isize::MAX);
+        let index = match self {
+            Self::Clamp => i.clamp(0, n - 1),
+            Self::Wrap => i.rem_euclid(n),
+            Self::Mirror if n == 1 => 0,
+            Self::Mirror => {
+                let period = 2 * (n - 1);
+                let folded = i.rem_euclid(period);
+                if folded < n {
+                    folded
+                } else {
+                    period - folded
+                }
+            }
+            Self::Constant if (0..n).contains(&i) => i,
+            Self::Constant => return None,
+        };
+        usize::try_from(index).ok()
+    }
+}
+
+/// Sigmas up to this use a sampled kernel; larger ones the recursive filter,
+/// whose cost does not grow with sigma.
+const GAUSSIAN_DIRECT_MAX_SIGMA: f64 = 3.0;
+
+/// Sampled kernels and recursive-filter padding extend this many sigmas.
+const GAUSSIAN_TRUNCATE: f64 = 4.0;
+
+/// Largest accepted sigma, in pixels.
+const GAUSSIAN_MAX_SIGMA: f64 = 1000.0;
+
+/// One-dimensional Gaussian smoother applied along rows, then columns.
+enum GaussianLine {
+    /// Normalized taps `g[0..=r]` of a symmetric sampled kernel.
+    Direct(Vec<f64>),
+    /// Young-van Vliet third-order recursive filter run forwards and
+    /// backwards: `y[n] = gain * x[n] + sum(feedback[k] * y[n - 1 - k])`.
+    Recursive {
+        gain: f64,
+        feedback: [f64; 3],
+        pad: usize,
+    },
+}
+
+impl GaussianLine {
+    fn new(sigma: f64) -> Self {
+        if sigma <= GAUSSIAN_DIRECT_MAX_SIGMA {
+            let radius = f64_to_usize((GAUSSIAN_TRUNCATE * sigma).ceil()).max(1);
+            let mut taps: Vec<f64> = (0..=radius)
+                .map(|k| (-0.5 * (usize_to_f64(k) / sigma).powi(2)).exp())
+                .collect();
+            let total = 2.0 * taps.iter().sum::<f64>() - taps[0];
+            for tap in &mut taps {
+                *tap /= total;
+            }
+            return Self::Direct(taps);
+        }
+        // Young, van Vliet & van Ginkel, "Recursive Gabor filtering" (2002):
+        // poles fitted at sigma 2 are raised to 1 / q, with q chosen so the
+        // forward-backward impulse response has variance sigma^2.
+        let (pair_re, pair_im, real_pole): (f64, f64, f64) = (1.416_50, 1.008_29, 1.865_43);
+        let pair_radius = f64::hypot(pair_re, pair_im);
+        let pair_angle = f64::atan2(pair_im, pair_re);
+        let scaled = |q: f64| {
+            let radius = pair_radius.powf(1.0 / q);
+            let angle = pair_angle / q;
+            (
+                radius * angle.cos(),
+                radius * angle.sin(),
+                real_pole.powf(1.0 / q),
+            )
+        };
+        // Variance 2 * sum(d / (d - 1)^2) over the poles d; it grows with q.
+        let variance = |q: f64| {
+            let (re, im, real) = scaled(q);
+            let (dr, di) = (re - 1.0, im);
+            let (sr, si) = (dr * dr - di * di, 2.0 * dr * di);
+            let pair = 2.0 * (re * sr + im * si) / (sr * sr + si * si);
+            2.0 * (pair + real / ((real - 1.0) * (real - 1.0)))
+        };
+        let (mut low, mut high) = (0.1, 2.0 * sigma);
+        for _ in 0..64 {
+            let mid = f64::midpoint(low, high);
+            if variance(mid) < sigma * sigma {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let (re, im, real) = scaled(f64::midpoint(low, high));
+        // Feedback taps of 1 / ((1 - p z^-1)(1 - conj(p) z^-1)(1 - r z^-1)), p = 1 / d.
+        let pair_norm = re * re + im * im;
+        let (p_re, p_abs2, r) = (re / pair_norm, 1.0 / pair_norm, 1.0 / real);
+        let feedback = [2.0 * p_re + r, -(p_abs2 + 2.0 * p_re * r), p_abs2 * r];
+        Self::Recursive {
+            gain: 1.0 - feedback.iter().sum::<f64>(),
+            feedback,
+            pad: f64_to_usize((GAUSSIAN_TRUNCATE * sigma).ceil()),
+        }
+    }
+
+    fn pad(&self) -> usize {
+        match self {
+            Self::Direct(taps) => taps.len() - 1,
+            Self::Recursive { pad, .. } => *pad,
+        }
+    }
+
+    /// Smooths `line` in place; `padded` is scratch for the edge-extended copy.
+    fn apply(&self, line: &mut [f64], padded: &mut Vec<f64>, edge: EdgeMode) {
+        let n = line.len();
+        let pad = self.pad();
+        padded.clear();
+        padded.resize(n + 2 * pad, 0.0);
+        padded[pad..pad + n].copy_from_slice(line);
+        let offset = isize::try_from(pad).unwrap_or(isize::MAX);
+        let outside = (0..pad).chain(pad + n..n + 2 * pad);
+        for i in outside {
+            let position = isize::try_from(i).unwrap_or(isize::MAX) - offset;
+            padded[i] = edge
+                .source_index(position, n)
+                .map_or(0.0, |index| line[index]);
+        }
+
+        match self {
+            Self::Direct(taps) => {
+                for (i, out) in line.iter_mut().enumerate() {
+                    let centre = i + pad;
+                    *out = taps[0] * padded[centre]
+                        + taps[1..]
+                            .iter()
+                            .enumerate()
+                            .map(|(k, tap)| tap * (padded[centre - k - 1] + padded[centre + k + 1]))
+                            .sum::<f64>();
+                }
+            }
+            Self::Recursive { gain, feedback, .. } => {
+                // Start each pass in the steady state of its first input sample.
+                let mut history = [padded[0]; 3];
+                for value in padded.iter_mut() {
+                    *value = recursive_step(*value, *gain, feedback, &mut history);
+                }
+                history = [padded[padded.len() - 1]; 3];
+                for value in padded.iter_mut().rev() {
+                    *value = recursive_step(*value, *gain, feedback, &mut history);
+                }
+                line.copy_from_slice(&padded[pad..pad + n]);
+            }
+        }
+    }
+
+    /// Smooths a row-major plane `w` samples wide along rows, then columns;
+    /// `transposed` is scratch of the same size.
+    fn blur_plane(&self, plane: &mut [f64], transposed: &mut [f64], w: usize, edge: EdgeMode) {
+        let h = plane.len() / w;
+        let mut padded = Vec::new();
+        for row in plane.chunks_exact_mut(w) {
+            self.apply(row, &mut padded, edge);
+        }
+        // Columns are filtered as rows of the transpose, which keeps reads contiguous.
+        transpose(plane, transposed, w, h);
+        for column in transposed.chunks_exact_mut(h) {
+            self.apply(column, &mut padded, edge);
+        }
+        transpose(transposed, plane, h, w);
+    }
+}
+
+fn recursive_step(input: f64, gain: f64, feedback: &[f64; 3], history: &mut [f64; 3]) -> f64 {
+    let output = gain * input
+        + feedback[0] * history[0]
+        + feedback[1] * history[1]
+        + feedback[2] * history[2];
+    *history = [output, history[0], history[1]];
+    output
+}
+
+/// Square tiles `transpose` copies at a time, small enough to stay in cache.
+const TRANSPOSE_TILE: usize = 32;
+
+/// Writes the transpose of the row-major `width` x `height` grid `src` to `dst`.
+fn transpose(src: &[f64], dst: &mut [f64], width: usize, height: usize) {
+    for y0 in (0..height).step_by(TRANSPOSE_TILE) {
+        for x0 in (0..width).step_by(TRANSPOSE_TILE) {
+            for y in y0..(y0 + TRANSPOSE_TILE).min(height) {
+                for x in x0..(x0 + TRANSPOSE_TILE).min(width) {
+                    dst[x * height + y] = src[y * width + x];
+                }
+            }
+        }
+    }
+}
+
+/// Gaussian blur of the colour channels of an RGBA image (alpha is kept).
+/// `sigma` is in pixels, in `(0, 1000]`. Up to sigma 3 a sampled kernel
+/// spanning four sigmas is used; above that a recursive approximation whose
+/// cost per pixel is constant. `edge` selects how the border is extended.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if the image size
+/// overflows, `BufferTooSmall` if `data` holds fewer than `width * height * 4` bytes,
+/// and `InvalidArgument` if `sigma` is outside `(0, 1000]`.
+#[wasm_bindgen]
+pub fn gaussian_blur(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    sigma: f64,
+    edge: EdgeMode,
+) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "gaussian_blur")?;
+    require_len(data.len(), bytes, "gaussian_blur: data")?;
+    if !(sigma > 0.0 && sigma <= GAUSSIAN_MAX_SIGMA) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("gaussian_blur: sigma must be in (0, {GAUSSIAN_MAX_SIGMA}], got {sigma}"),
+        )
+        .into());
+    }
+
+    let smoother = GaussianLine::new(sigma);
+    let pixels = &mut data[..bytes];
+    let mut plane = vec![0.0; w * h];
+    let mut transposed = vec![0.0; w * h];
+    for channel in 0..3 {
+        for (value, pixel) in plane.iter_mut().zip(pixels.chunks_exact(4)) {
+            *value = f64::from(pixel[channel]);
+        }
+        smoother.blur_plane(&mut plane, &mut transposed, w, edge);
+        for (pixel, value) in pixels.chunks_exact_mut(4).zip(&plane) {
+            pixel[channel] = clamp_to_u8(*value);
+        }
+    }
+    Ok(())
+}
+
+/// Largest entry of a kernel that is not the product of its pivot column and
+/// row, relative to the largest entry, for the kernel to count as separable.
+const SEPARABLE_TOLERANCE: f64 = 1e-6;
+
+/// Splits a row-major `kw` x `kh` kernel into `column * row` when it has rank one.
+fn separate_kernel(kernel: &[f32], kw: usize) -> Option<(Vec<f32>, Vec<f32>)> {
+    let (pivot, peak) = kernel
+        .iter()
+        .map(|&k| f64::from(k).abs())
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if peak == 0.0 {
+        return None;
+    }
+    let (pivot_row, pivot_column) = (pivot / kw, pivot % kw);
+    let at = |row: usize, column: usize| f64::from(kernel[row * kw + column]);
+    let column: Vec<f64> = (0..kernel.len() / kw)
+        .map(|j| at(j, pivot_column))
+        .collect();
+    let row: Vec<f64> = (0..kw)
+        .map(|i| at(pivot_row, i) / at(pivot_row, pivot_column))
+        .collect();
+    let separable = kernel.chunks_exact(kw).zip(&column).all(|(values, c)| {
+        values
+            .iter()
+            .zip(&row)
+            .all(|(&k, r)| (f64::from(k) - c * r).abs() <= SEPARABLE_TOLERANCE * peak)
+    });
+    separable.then(|| {
+        (
+            column.into_iter().map(f64_to_f32).collect(),
+            row.into_iter().map(f64_to_f32).collect(),
+        )
+    })
+}
+
+/// `sum(weights[i] * pixels[i * stride..][..4])`: a weighted sum of RGBA
+/// quads spaced `stride` floats apart.
+fn weighted_pixel_sum(pixels: &[f32], stride: usize, weights: &[f32]) -> [f32; 4] {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    // SAFETY: The SIMD helper only loads quads it has bounds-checked through slicing.
+    unsafe {
+        return weighted_pixel_sum_simd128(pixels, stride, weights);
+    }
+    weighted_pixel_sum_fallback(pixels, stride, weights)
+}
+
+fn weighted_pixel_sum_fallback(pixels: &[f32], stride: usize, weights: &[f32]) -> [f32; 4] {
+    let mut acc = [0.0f32; 4];
+    for (i, &weight) in weights.iter().enumerate() {
+        let quad = &pixels[i * stride..i * stride + 4];
+        for lane in 0..4 {
+            acc[lane] += weight * quad[lane];
+        }
+    }
+    acc
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[target_feature(enable = "simd128")]
+unsafe fn weighted_pixel_sum_simd128(pixels: &[f32], stride: usize, weights: &[f32]) -> [f32; 4] {
+    use core::arch::wasm32::*;
+
+    let mut acc = f32x4_splat(0.0);
+    for (i, &weight) in weights.iter().enumerate() {
+        let quad = &pixels[i * stride..i * stride + 4];
+        // SAFETY: `quad` is a bounds-checked run of 4 f32s, one unaligned v128 load.
+        let v = v128_load(quad.as_ptr() as *const v128);
+        acc = f32x4_add(acc, f32x4_mul(v, f32x4_splat(weight)));
+    }
+
+    let mut out = [0f32; 4];
+    // SAFETY: `out` has 4 f32 lanes and is valid destination for one v128 store.
+    v128_store(out.as_mut_ptr() as *mut v128, acc);
+    out
+}
+
+/// Convolves an RGBA image with a row-major `kernel_width` x `kernel_height`
+/// kernel, anchored at `((kernel_width - 1) / 2, (kernel_height - 1) / 2)` and
+/// applied as written (not flipped), like editors' convolution matrices. Each
+/// channel becomes `sum / divisor + bias`, rounded and clamped; `divisor` 0
+/// means the kernel sum (or 1 if that is 0). Alpha is convolved only when
+/// `include_alpha` is set. Rank-one kernels run as two 1D passes.
+///
+/// # Errors
+///
+/// As `gaussian_blur` for the image, plus `InvalidDimensions` if a kernel side is zero,
+/// `LengthMismatch` if `kernel` does not hold `kernel_width * kernel_height` values,
+/// `InvalidArgument` if a kernel value, `divisor` or `bias` is not finite, and
+/// `SizeOverflow` if the padded image does not fit in memory.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn convolve_rgba(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    kernel: &[f32],
+    kernel_width: u32,
+    kernel_height: u32,
+    divisor: f32,
+    bias: f32,
+    edge: EdgeMode,
+    include_alpha: bool,
+) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "convolve_rgba")?;
+    require_len(data.len(), bytes, "convolve_rgba: data")?;
+    let kw = usize::try_from(kernel_width).unwrap_or(usize::MAX);
+    let kh = usize::try_from(kernel_height).unwrap_or(usize::MAX);
+    let taps = grid_len(kw, kh, "convolve_rgba: kernel")?;
+    if kernel.len() != taps {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!(
+                "convolve_rgba: kernel has {} values, {kw}x{kh} needs {taps}",
+                kernel.len()
+            ),
+        )
+        .into());
+    }
+    for &k in kernel {
+        require_finite(f64::from(k), "convolve_rgba: kernel value")?;
+    }
+    require_finite(f64::from(divisor), "convolve_rgba: divisor")?;
+    require_finite(f64::from(bias), "convolve_rgba: bias")?;
+    let (pw, ph) = (w.saturating_add(kw - 1), h.saturating_add(kh - 1));
+    let padded_len = grid_len(pw, ph, "convolve_rgba")?
+        .checked_mul(4)
+        .ok_or_else(|| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                "convolve_rgba: padded image does not fit in memory",
+            )
+        })?;
+
+    let divisor = if divisor == 0.0 {
+        let total: f32 = kernel.iter().sum();
+        if total == 0.0 {
+            1.0
+        } else {
+            total
+        }
+    } else {
+        divisor
+    };
+
+    // Edge-extended copy of the image as f32 RGBA quads.
+    let pixels = &mut data[..bytes];
+    let to_offset = |i: usize, anchor: usize| {
+        isize::try_from(i).unwrap_or(isize::MAX) - isize::try_from(anchor).unwrap_or(isize::MAX)
+    };
+    let columns: Vec<Option<usize>> = (0..pw)
+        .map(|x| edge.source_index(to_offset(x, (kw - 1) / 2), w))
+        .collect();
+    let mut padded = vec![0.0f32; padded_len];
+    for (y, row) in padded.chunks_exact_mut(pw * 4).enumerate() {
+        let Some(sy) = edge.source_index(to_offset(y, (kh - 1) / 2), h) else {
+            continue;
+        };
+        for (quad, column) in row.chunks_exact_mut(4).zip(&columns) {
+            if let Some(sx) = column {
+                let source = &pixels[(sy * w + sx) * 4..(sy * w + sx) * 4 + 4];
+                for (value, &byte) in quad.iter_mut().zip(source) {
+                    *value = f32::from(byte);
+                }
+            }
+        }
+    }
+
+    let channels = if include_alpha { 4 } else { 3 };
+    let mut store = |index: usize, acc: [f32; 4]| {
+        let pixel = &mut pixels[index * 4..index * 4 + 4];
+        for (byte, value) in pixel.iter_mut().zip(acc).take(channels) {
+            *byte = clamp_to_u8(f64::from(value / divisor + bias));
+        }
+    };
+
+    if let Some((column, row)) = separate_kernel(kernel, kw) {
+        let mut rows = vec![0.0f32; w * ph * 4];
+        for (y, out) in rows.chunks_exact_mut(w * 4).enumerate() {
+            for (x, quad) in out.chunks_exact_mut(4).enumerate() {
+                let start = (y * pw + x) * 4;
+                quad.copy_from_slice(&weighted_pixel_sum(&padded[start..], 4, &row));
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let start = (y * w + x) * 4;
+                store(
+                    y * w + x,
+                    weighted_pixel_sum(&rows[start..], w * 4, &column),
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = [0.0f32; 4];
+            for (j, weights) in kernel.chunks_exact(kw).enumerate() {
+                let start = ((y + j) * pw + x) * 4;
+                let sum = weighted_pixel_sum(&padded[start..], 4, weights);
+                for lane in 0..4 {
+                    acc[lane] += sum[lane];
+                }
+            }
+            store(y * w + x, acc);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// EDGE DETECTION
+// ============================================================================
+
+/// 3x3 derivative kernels for `image_gradient`, `gradient_magnitude` and `canny`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientOperator {
+    /// Central difference smoothed with `[1, 2, 1]`.
+    Sobel = 0,
+    /// Central difference smoothed with `[3, 10, 3]`; the most rotation-invariant.
+    Scharr = 1,
+    /// Central difference averaged over `[1, 1, 1]`.
+    Prewitt = 2,
+}
+
+impl GradientOperator {
+    /// Smoothing taps across the derivative and their sum.
+    fn smoothing(self) -> ([f64; 3], f64) {
+        match self {
+            Self::Sobel => ([1.0, 2.0, 1.0], 4.0),
+            Self::Scharr => ([3.0, 10.0, 3.0], 16.0),
+            Self::Prewitt => ([1.0, 1.0, 1.0], 3.0),
+        }
+    }
+}
+
+/// Horizontal and vertical derivatives of a `w`-wide plane with clamped
+/// borders, scaled so a black-to-white step reads 255.
+fn plane_gradients(plane: &[f64], w: usize, operator: GradientOperator) -> (Vec<f64>, Vec<f64>) {
+    let h = plane.len() / w;
+    let (taps, total) = operator.smoothing();
+    let at = |x: usize, y: usize| plane[y.min(h - 1) * w + x.min(w - 1)];
+    let mut gx = vec![0.0; plane.len()];
+    let mut gy = vec![0.0; plane.len()];
+    for y in 0..h {
+        let rows = [y.saturating_sub(1), y, y + 1];
+        for x in 0..w {
+            let columns = [x.saturating_sub(1), x, x + 1];
+            let mut dx = 0.0;
+            let mut dy = 0.0;
+            for k in 0..3 {
+                dx += taps[k] * (at(columns[2], rows[k]) - at(columns[0], rows[k]));
+                dy += taps[k] * (at(columns[k], rows[2]) - at(columns[k], rows[0]));
+            }
+            gx[y * w + x] = dx / total;
+            gy[y * w + x] = dy / total;
+        }
+    }
+    (gx, gy)
+}
+
+/// Per-pixel gradient of an RGBA image's luminance: `magnitude` is
+/// `hypot(gx, gy)` (a black-to-white step reads 255, at most 255 * sqrt(2))
+/// and `direction` is `atan2(gy, gx)` in radians with y pointing down.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if the image size
+/// overflows, and `BufferTooSmall` if `data` holds fewer than `width * height * 4`
+/// bytes or `magnitude` or `direction` fewer than `width * height` values.
+#[wasm_bindgen]
+pub fn image_gradient(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    operator: GradientOperator,
+    magnitude: &mut [f32],
+    direction: &mut [f32],
+) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "image_gradient")?;
+    require_len(data.len(), bytes, "image_gradient: data")?;
+    require_len(magnitude.len(), w * h, "image_gradient: magnitude")?;
+    require_len(direction.len(), w * h, "image_gradient: direction")?;
+
+    let (gx, gy) = plane_gradients(&luminance_plane(&data[..bytes]), w, operator);
+    for (((m, d), dx), dy) in magnitude
+        .iter_mut()
+        .zip(direction.iter_mut())
+        .zip(&gx)
+        .zip(&gy)
+    {
+        *m = f64_to_f32(dx.hypot(*dy));
+        *d = f64_to_f32(dy.atan2(*dx));
+    }
+    Ok(())
+}
+
+/// Gradient magnitude of an RGBA image as a single-channel map, one byte per
+/// pixel, saturating at 255 (a full black-to-white step).
+///
+/// # Errors
+///
+/// As `image_gradient`, with `out` as the only output.
+#[wasm_bindgen]
+pub fn gradient_magnitude(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    operator: GradientOperator,
+    out: &mut [u8],
+) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "gradient_magnitude")?;
+    require_len(data.len(), bytes, "gradient_magnitude: data")?;
+    require_len(out.len(), w * h, "gradient_magnitude: out")?;
+
+    let (gx, gy) = plane_gradients(&luminance_plane(&data[..bytes]), w, operator);
+    for ((value, dx), dy) in out.iter_mut().zip(&gx).zip(&gy) {
+        *value = clamp_to_u8(dx.hypot(*dy));
+    }
+    Ok(())
+}
+
+/// Pixel offsets of the two neighbours along a gradient, by direction sector.
+fn gradient_neighbours(dx: f64, dy: f64) -> [(isize, isize); 2] {
+    // tan(22.5 deg): beyond this ratio the gradient leans towards the other axis.
+    const TAN_22_5: f64 = 0.414_213_562_373_095_1;
+    let (ax, ay) = (dx.abs(), dy.abs());
+    if ay <= TAN_22_5 * ax {
+        [(1, 0), (-1, 0)]
+    } else if ax <= TAN_22_5 * ay {
+        [(0, 1), (0, -1)]
+    } else if (dx > 0.0) == (dy > 0.0) {
+        [(1, 1), (-1, -1)]
+    } else {
+        [(1, -1), (-1, 1)]
+    }
+}
+
+/// Thins ridges to one pixel: keeps local maxima of `magnitude` along the
+/// gradient (ties go to the first neighbour), treating pixels off the image as 0.
+fn suppress_non_maxima(magnitude: &[f64], gx: &[f64], gy: &[f64], w: usize) -> Vec<f64> {
+    let h = magnitude.len() / w;
+    let neighbour = |x: usize, y: usize, (ox, oy): (isize, isize)| {
+        let nx = x.checked_add_signed(ox).filter(|&nx| nx < w);
+        let ny = y.checked_add_signed(oy).filter(|&ny| ny < h);
+        nx.zip(ny).map_or(0.0, |(nx, ny)| magnitude[ny * w + nx])
+    };
+    let mut ridge = vec![0.0; magnitude.len()];
+    for (i, value) in ridge.iter_mut().enumerate() {
+        let (x, y) = (i % w, i / w);
+        let [ahead, behind] = gradient_neighbours(gx[i], gy[i]);
+        if magnitude[i] > neighbour(x, y, ahead) && magnitude[i] >= neighbour(x, y, behind) {
+            *value = magnitude[i];
+        }
+    }
+    ridge
+}
+
+/// Marks ridge pixels of at least `high` with 255 and grows them through
+/// 8-connected ridge pixels of at least `low`; everything else becomes 0.
+fn hysteresis(ridge: &[f64], w: usize, low: f64, high: f64, out: &mut [u8]) {
+    let h = ridge.len() / w;
+    let candidate = |value: f64, threshold: f64| value > 0.0 && value >= threshold;
+    out.fill(0);
+    let mut stack = Vec::new();
+    for (seed, &value) in ridge.iter().enumerate() {
+        if out[seed] != 0 || !candidate(value, high) {
+            continue;
+        }
+        out[seed] = u8::MAX;
+        stack.push(seed);
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                    let next = ny * w + nx;
+                    if out[next] == 0 && candidate(ridge[next], low) {
+                        out[next] = u8::MAX;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Canny edge detection on an RGBA image's luminance: Gaussian blur of
+/// `sigma` pixels (0 skips it), gradients, non-maximum suppression, then
+/// hysteresis keeping pixels above `low` that connect (8-way) to one above
+/// `high`. Thresholds are in `gradient_magnitude` units. Writes 255 for edge
+/// pixels and 0 elsewhere into the single-channel `out`; returns the edge count.
+///
+/// # Errors
+///
+/// As `gradient_magnitude`, plus `InvalidArgument` if `sigma` is outside `[0, 1000]` or
+/// the thresholds do not satisfy `0 <= low <= high`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn canny(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    sigma: f64,
+    low: f64,
+    high: f64,
+    operator: GradientOperator,
+    out: &mut [u8],
+) -> Result<usize, JsError> {
+    let (w, h, bytes) = image_dims(width, height, "canny")?;
+    require_len(data.len(), bytes, "canny: data")?;
+    require_len(out.len(), w * h, "canny: out")?;
+    if !(0.0..=GAUSSIAN_MAX_SIGMA).contains(&sigma) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("canny: sigma must be in [0, {GAUSSIAN_MAX_SIGMA}], got {sigma}"),
+        )
+        .into());
+    }
+    require_finite(high, "canny: high threshold")?;
+    let valid = low >= 0.0 && low <= high;
+    if !valid {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("canny: thresholds need 0 <= low <= high, got {low} and {high}"),
+        )
+        .into());
+    }
+
+    let mut plane = luminance_plane(&data[..bytes]);
+    if sigma > 0.0 {
+        let mut transposed = vec![0.0; w * h];
+        GaussianLine::new(sigma).blur_plane(&mut plane, &mut transposed, w, EdgeMode::Clamp);
+    }
+    let (gx, gy) = plane_gradients(&plane, w, operator);
+    let magnitude: Vec<f64> = gx.iter().zip(&gy).map(|(dx, dy)| dx.hypot(*dy)).collect();
+
+    let ridge = suppress_non_maxima(&magnitude, &gx, &gy, w);
+    let output = &mut out[..w * h];
+    hysteresis(&ridge, w, low, high, output);
+    Ok(output.iter().filter(|&&value| value != 0).count())
+}
+
+// ============================================================================
+// FFT & SIGNAL PROCESSING
+// ============================================================================
+
+/// Butterfly scheme used by the power-of-two complex FFT.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FftAlgorithm {
+    /// Iterative in-place radix-2 decimation in time.
+    Radix2 = 0,
+    /// In-place split-radix (fewest real multiplications for power-of-two sizes).
+    SplitRadix = 1,
+}
+
+/// Quantity derived from each complex bin by `fft_spectrum`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectrumKind {
+    /// `|X[k]|`
+    Magnitude = 0,
+    /// `atan2(im, re)` in radians, in `[-pi, pi]`.
+    Phase = 1,
+    /// `|X[k]|^2`
+    Power = 2,
+}
+
+/// Twiddle factors `w^k = e^{-2 pi i k / n}`. For even `n` only `k < n / 2` is
+/// stored; the upper half follows from `w^{k + n/2} = -w^k`.
+struct Twiddles {
+    n: usize,
+    re: Vec<f64>,
+    im: Vec<f64>,
+}
+
+impl Twiddles {
+    fn new(n: usize) -> Self {
+        let stored = if n.is_multiple_of(2) { n / 2 } else { n };
+        let n_f64 = usize_to_f64(n);
+        let (re, im) = (0..stored)
+            .map(|k| {
+                let angle = -2.0 * std::f64::consts::PI * usize_to_f64(k) / n_f64;
+                let (sin, cos) = angle.sin_cos();
+                (cos, sin)
+            })
+            .unzip();
+        Self { n, re, im }
+    }
+
+    /// `w^k` for any `k < n`.
+    fn get(&self, k: usize) -> (f64, f64) {
+        let stored = self.re.len();
+        if k < stored {
+            (self.re[k], self.im[k])
+        } else {
+            (-self.re[k - stored], -self.im[k - stored])
+        }
+    }
+}
+
+/// Transform lengths whose precomputed state is kept between calls, per thread.
+const FFT_CACHE_CAPACITY: usize = 16;
+
+/// Least-recently-used cache keyed by transform length, holding at most
+/// `FFT_CACHE_CAPACITY` entries. Evicted state stays alive for as long as
+/// something else (an `FftPlan`, a mixed-radix kernel) holds its `Rc`.
+struct LenCache<T> {
+    /// Oldest first; a hit moves the entry to the back.
+    entries: Vec<(usize, Rc<T>)>,
+}
+
+impl<T> LenCache<T> {
+    const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    fn get(&mut self, n: usize) -> Option<Rc<T>> {
+        let index = self.entries.iter().position(|&(len, _)| len == n)?;
+        let entry = self.entries.remove(index);
+        let value = Rc::clone(&entry.1);
+        self.entries.push(entry);
+        Some(value)
+    }
+
+    fn insert(&mut self, n: usize, value: Rc<T>) {
+        if self.entries.len() == FFT_CACHE_CAPACITY {
+            self.entries.remove(0);
+        }
+        self.entries.push((n, value));
+    }
+}
+
+thread_local! {
+    static TWIDDLE_CACHE: RefCell<LenCache<Twiddles>> = const { RefCell::new(LenCache::new()) };
+}
+
+/// Shared twiddle table for size `n`, computed on first use.
+fn twiddles_for(n: usize) -> Rc<Twiddles> {
+    TWIDDLE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.get(n).unwrap_or_else(|| {
+            let twiddles = Rc::new(Twiddles::new(n));
+            cache.insert(n, Rc::clone(&twiddles));
+            twiddles
+        })
+    })
+}
+
+/// In-place forward transform of a power-of-two length split complex buffer.
+fn fft_pow2(re: &mut [f64], im: &mut [f64], algorithm: FftAlgorithm) {
+    let n = re.len();
+    if n <= 1 {
+        return;
+    }
+    let twiddles = twiddles_for(n);
+    match algorithm {
+        FftAlgorithm::Radix2 => fft_radix2(re, im, &twiddles),
+        FftAlgorithm::SplitRadix => fft_split_radix(re, im, &twiddles),
+    }
+}
+
+/// Inverse transform through a forward one: `ifft(x) = conj(fft(conj(x))) / n`.
+fn inverse_via_forward(
+    re: &mut [f64],
+    im: &mut [f64],
+    forward: impl FnOnce(&mut [f64], &mut [f64]),
+) {
+    let n = re.len();
+    if n == 0 {
+        return;
+    }
+    for value in im.iter_mut() {
+        *value = -*value;
+    }
+    forward(re, im);
+    let scale = 1.0 / usize_to_f64(n);
+    for value in re.iter_mut() {
+        *value *= scale;
+    }
+    for value in im.iter_mut() {
+        *value *= -scale;
+    }
+}
+
+fn bit_reverse_permute(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+}
+
+fn fft_radix2(re: &mut [f64], im: &mut [f64], twiddles: &Twiddles) {
+    let n = re.len();
+    bit_reverse_permute(re, im);
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let (wr, wi) = twiddles.get(k * step);
+                let a = start + k;
+                let b = a + half;
+                let tr = re[b] * wr - im[b] * wi;
+                let ti = re[b] * wi + im[b] * wr;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// In-place split-radix: decimation in frequency with L-shaped butterflies
+/// (Sorensen, Heideman and Burrus, 1986), leaving the output bit-reversed.
+///
+/// Each stage of span `n2` turns every block `x[i0..i0 + n2]` into a half-size
+/// block at `i0` and two twiddled quarter-size blocks at `i0 + n2/2` and
+/// `i0 + 3n2/4`. Blocks are visited in the order the recursion would create
+/// them, without a stack or scratch buffers.
+fn fft_split_radix(re: &mut [f64], im: &mut [f64], twiddles: &Twiddles) {
+    let n = re.len();
+    let mut n2 = n;
+    while n2 > 2 {
+        let n4 = n2 / 4;
+        let step = twiddles.n / n2;
+        for j in 0..n4 {
+            let w1 = twiddles.get(j * step);
+            let w3 = twiddles.get(3 * j * step);
+            let mut start = j;
+            let mut stride = 2 * n2;
+            while start < n {
+                for i0 in (start..n).step_by(stride) {
+                    split_radix_butterfly(re, im, [i0, i0 + n4, i0 + 2 * n4, i0 + 3 * n4], w1, w3);
+                }
+                start = 2 * stride - n2 + j;
+                stride *= 4;
+            }
+        }
+        n2 /= 2;
+    }
+
+    let mut start = 0;
+    let mut stride = 4;
+    while start < n {
+        for i0 in (start..n).step_by(stride) {
+            let (xr, xi) = (re[i0], im[i0]);
+            re[i0] = xr + re[i0 + 1];
+            im[i0] = xi + im[i0 + 1];
+            re[i0 + 1] = xr - re[i0 + 1];
+            im[i0 + 1] = xi - im[i0 + 1];
+        }
+        start = 2 * stride - 2;
+        stride *= 4;
+    }
+    bit_reverse_permute(re, im);
+}
+
+/// One L-shaped butterfly over indices `[i0, i1, i2, i3]`, a quarter span apart,
+/// with twiddles `w^j` and `w^{3j}`.
+fn split_radix_butterfly(
+    re: &mut [f64],
+    im: &mut [f64],
+    [i0, i1, i2, i3]: [usize; 4],
+    (w1r, w1i): (f64, f64),
+    (w3r, w3i): (f64, f64),
+) {
+    let (ar, ai) = (re[i0] - re[i2], im[i0] - im[i2]);
+    let (br, bi) = (re[i1] - re[i3], im[i1] - im[i3]);
+    re[i0] += re[i2];
+    im[i0] += im[i2];
+    re[i1] += re[i3];
+    im[i1] += im[i3];
+    // a - i*b feeds the w^j output and a + i*b the w^{3j} one.
+    let (zr, zi) = (ar + bi, ai - br);
+    let (yr, yi) = (ar - bi, ai + br);
+    re[i2] = zr * w1r - zi * w1i;
+    im[i2] = zr * w1i + zi * w1r;
+    re[i3] = yr * w3r - yi * w3i;
+    im[i3] = yr * w3i + yi * w3r;
+}
+
+/// Largest radix handled by the mixed-radix kernel; lengths with a larger prime
+/// factor go through Bluestein.
+const MAX_RADIX: usize = 5;
+
+/// Precomputed state for one transform length.
+enum FftKernel {
+    /// Lengths 0 and 1.
+    Identity,
+    Pow2,
+    /// Cooley-Tukey over radices 4, 2, 3 and 5, outermost factor first.
+    MixedRadix {
+        factors: Vec<usize>,
+        twiddles: Rc<Twiddles>,
+    },
+    /// Chirp-z: the length-`n` DFT as a circular convolution of power-of-two length `m`.
+    Bluestein {
+        m: usize,
+        chirp_re: Vec<f64>,
+        chirp_im: Vec<f64>,
+        filter_re: Vec<f64>,
+        filter_im: Vec<f64>,
+    },
+}
+
+thread_local! {
+    static PLAN_CACHE: RefCell<LenCache<FftKernel>> = const { RefCell::new(LenCache::new()) };
+}
+
+/// Shared kernel for length `n`, built on first use.
+fn kernel_for(n: usize) -> Result<Rc<FftKernel>, WasmError> {
+    if let Some(kernel) = PLAN_CACHE.with(|cache| cache.borrow_mut().get(n)) {
+        return Ok(kernel);
+    }
+    let kernel = Rc::new(FftKernel::new(n)?);
+    PLAN_CACHE.with(|cache| cache.borrow_mut().insert(n, Rc::clone(&kernel)));
+    Ok(kernel)
+}
+
+/// Splits `n` into radices 4, 2, 3 and 5, or returns `None` if another prime divides it.
+fn smooth_factors(mut n: usize) -> Option<Vec<usize>> {
+    let mut factors = Vec::new();
+    for radix in [4, 2, 3, 5] {
+        while n.is_multiple_of(radix) {
+            factors.push(radix);
+            n /= radix;
+        }
+    }
+    (n == 1).then_some(factors)
+}
+
+impl FftKernel {
+    fn new(n: usize) -> Result<Self, WasmError> {
+        if n <= 1 {
+            return Ok(Self::Identity);
+        }
+        if n.is_power_of_two() {
+            return Ok(Self::Pow2);
+        }
+        if let Some(factors) = smooth_factors(n) {
+            return Ok(Self::MixedRadix {
+                factors,
+                twiddles: twiddles_for(n),
+            });
+        }
+        Self::bluestein(n)
+    }
+
+    fn bluestein(n: usize) -> Result<Self, WasmError> {
+        let m = n
+            .checked_mul(2)
+            .and_then(|len| (len - 1).checked_next_power_of_two())
+            .filter(|&m| m.checked_mul(2 * std::mem::size_of::<f64>()).is_some())
+            .ok_or_else(|| {
+                WasmError::new(
+                    ErrorCode::SizeOverflow,
+                    format!("FftPlan: Bluestein buffer for length {n} overflows"),
+                )
+            })?;
+
+        // w_k = e^{-i pi k^2 / n}; k^2 is reduced mod 2n first to keep the angle small.
+        let period = 2 * n as u128;
+        let n_f64 = usize_to_f64(n);
+        let (chirp_re, chirp_im): (Vec<f64>, Vec<f64>) = (0..n as u128)
+            .map(|k| {
+                let phase = (k * k) % period;
+                #[allow(clippy::cast_precision_loss)]
+                let angle = -std::f64::consts::PI * phase as f64 / n_f64;
+                let (sin, cos) = angle.sin_cos();
+                (cos, sin)
+            })
+            .unzip();
+
+        let mut filter_re = vec![0.0; m];
+        let mut filter_im = vec![0.0; m];
+        filter_re[0] = chirp_re[0];
+        filter_im[0] = -chirp_im[0];
+        for k in 1..n {
+            filter_re[k] = chirp_re[k];
+            filter_im[k] = -chirp_im[k];
+            filter_re[m - k] = chirp_re[k];
+            filter_im[m - k] = -chirp_im[k];
+        }
+        fft_pow2(&mut filter_re, &mut filter_im, FftAlgorithm::SplitRadix);
+
+        Ok(Self::Bluestein {
+            m,
+            chirp_re,
+            chirp_im,
+            filter_re,
+            filter_im,
+        })
+    }
+
+    /// In-place inverse transform, scaled by `1 / n`.
+    fn inverse(&self, re: &mut [f64], im: &mut [f64]) {
+        inverse_via_forward(re, im, |re, im| self.forward(re, im));
+    }
+
+    /// In-place forward transform; `re` and `im` have the planned length.
+    fn forward(&self, re: &mut [f64], im: &mut [f64]) {
+        match self {
+            Self::Identity => {}
+            Self::Pow2 => fft_pow2(re, im, FftAlgorithm::SplitRadix),
+            Self::MixedRadix { factors, twiddles } => {
+                let n = re.len();
+                let mut out_re = vec![0.0; n];
+                let mut out_im = vec![0.0; n];
+                mixed_radix_rec(re, im, 0, 1, &mut out_re, &mut out_im, factors, twiddles);
+                re.copy_from_slice(&out_re);
+                im.copy_from_slice(&out_im);
+            }
+            Self::Bluestein {
+                m,
+                chirp_re,
+                chirp_im,
+                filter_re,
+                filter_im,
+            } => {
+                let m = *m;
+                let mut a_re = vec![0.0; m];
+                let mut a_im = vec![0.0; m];
+                for k in 0..re.len() {
+                    a_re[k] = re[k] * chirp_re[k] - im[k] * chirp_im[k];
+                    a_im[k] = re[k] * chirp_im[k] + im[k] * chirp_re[k];
+                }
+                fft_pow2(&mut a_re, &mut a_im, FftAlgorithm::SplitRadix);
+                // Pointwise product, conjugated so the forward FFT below acts as an inverse.
+                for k in 0..m {
+                    let pr = a_re[k] * filter_re[k] - a_im[k] * filter_im[k];
+                    let pi = a_re[k] * filter_im[k] + a_im[k] * filter_re[k];
+                    a_re[k] = pr;
+                    a_im[k] = -pi;
+                }
+                fft_pow2(&mut a_re, &mut a_im, FftAlgorithm::SplitRadix);
+                let scale = 1.0 / usize_to_f64(m);
+                for k in 0..re.len() {
+                    let cr = a_re[k] * scale;
+                    let ci = -a_im[k] * scale;
+                    re[k] = cr * chirp_re[k] - ci * chirp_im[k];
+                    im[k] = cr * chirp_im[k] + ci * chirp_re[k];
+                }
+            }
+        }
+    }
+}
+
+/// Recursive decimation-in-time Cooley-Tukey: transforms the `out.len()` samples
+/// `input[offset + j * stride]` into `out` by splitting on `factors[0]` and
+/// recursing on the rest.
+#[allow(clippy::too_many_arguments)]
+fn mixed_radix_rec(
+    in_re: &[f64],
+    in_im: &[f64],
+    offset: usize,
+    stride: usize,
+    out_re: &mut [f64],
+    out_im: &mut [f64],
+    factors: &[usize],
+    twiddles: &Twiddles,
+) {
+    let n = out_re.len();
+    let Some((&radix, rest)) = factors.split_first() else {
+        out_re[0] = in_re[offset];
+        out_im[0] = in_im[offset];
+        return;
+    };
+
+    let m = n / radix;
+    for q in 0..radix {
+        let range = q * m..(q + 1) * m;
+        mixed_radix_rec(
+            in_re,
+            in_im,
+            offset + q * stride,
+            stride * radix,
+            &mut out_re[range.clone()],
+            &mut out_im[range],
+            rest,
+            twiddles,
+        );
+    }
+
+    let step = twiddles.n / n;
+    let radix_step = twiddles.n / radix;
+    let mut t_re = [0.0; MAX_RADIX];
+    let mut t_im = [0.0; MAX_RADIX];
+    for k in 0..m {
+        for q in 0..radix {
+            let (wr, wi) = twiddles.get(q * k * step);
+            let (zr, zi) = (out_re[q * m + k], out_im[q * m + k]);
+            t_re[q] = zr * wr - zi * wi;
+            t_im[q] = zr * wi + zi * wr;
+        }
+        for s in 0..radix {
+            let mut sum_re = 0.0;
+            let mut sum_im = 0.0;
+            for q in 0..radix {
+                let (wr, wi) = twiddles.get((q * s) % radix * radix_step);
+                sum_re += t_re[q] * wr - t_im[q] * wi;
+                sum_im += t_re[q] * wi + t_im[q] * wr;
+            }
+            out_re[s * m + k] = sum_re;
+            out_im[s * m + k] = sum_im;
+        }
+    }
+}
+
+/// Checks a split complex buffer pair and returns its length.
+fn check_split_complex(re_len: usize, im_len: usize, what: &str) -> Result<usize, WasmError> {
+    if re_len != im_len {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!("{what}: re has {re_len} elements but im has {im_len}"),
+        ));
+    }
+    Ok(re_len)
+}
+
+/// Checks an interleaved `[re0, im0, re1, im1, ...]` buffer and returns its complex length.
+fn check_interleaved(len: usize, what: &str) -> Result<usize, WasmError> {
+    if !len.is_multiple_of(2) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: interleaved buffer has odd length {len}"),
+        ));
+    }
+    Ok(len / 2)
+}
+
+fn require_pow2(n: usize, what: &str) -> Result<(), WasmError> {
+    if n > 0 && !n.is_power_of_two() {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: length {n} is not a power of two"),
+        ));
+    }
+    Ok(())
+}
+
+fn deinterleave(data: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    data.chunks_exact(2).map(|pair| (pair[0], pair[1])).unzip()
+}
+
+fn interleave(re: &[f64], im: &[f64], data: &mut [f64]) {
+    for ((pair, &r), &i) in data.chunks_exact_mut(2).zip(re).zip(im) {
+        pair[0] = r;
+        pair[1] = i;
+    }
+}
+
+/// In-place forward FFT of split real/imaginary f64 buffers (power-of-two length).
+/// Unnormalized: `X[k] = sum x[t] e^{-2 pi i k t / n}`.
+///
+/// # Errors
+///
+/// `LengthMismatch` if `re` and `im` differ in length, and `InvalidDimensions` if the
+/// length is not a power of two.
+#[wasm_bindgen]
+pub fn fft_complex(re: &mut [f64], im: &mut [f64], algorithm: FftAlgorithm) -> Result<(), JsError> {
+    let n = check_split_complex(re.len(), im.len(), "fft_complex")?;
+    require_pow2(n, "fft_complex")?;
+    fft_pow2(re, im, algorithm);
+    Ok(())
+}
+
+/// `fft_complex` for f32 buffers; the transform itself runs in f64.
+///
+/// # Errors
+///
+/// As `fft_complex`.
+#[wasm_bindgen]
+pub fn fft_complex_f32(
+    re: &mut [f32],
+    im: &mut [f32],
+    algorithm: FftAlgorithm,
+) -> Result<(), JsError> {
+    let n = check_split_complex(re.len(), im.len(), "fft_complex_f32")?;
+    require_pow2(n, "fft_complex_f32")?;
+    let mut re64: Vec<f64> = re.iter().copied().map(f64::from).collect();
+    let mut im64: Vec<f64> = im.iter().copied().map(f64::from).collect();
+    fft_pow2(&mut re64, &mut im64, algorithm);
+    store_f32(&re64, re);
+    store_f32(&im64, im);
+    Ok(())
+}
+
+/// In-place forward FFT of an interleaved `[re0, im0, re1, im1, ...]` f64 buffer.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `data` has odd length or its complex length is not a power of
+/// two.
+#[wasm_bindgen]
+pub fn fft_interleaved(data: &mut [f64], algorithm: FftAlgorithm) -> Result<(), JsError> {
+    let n = check_interleaved(data.len(), "fft_interleaved")?;
+    require_pow2(n, "fft_interleaved")?;
+    let (mut re, mut im) = deinterleave(data);
+    fft_pow2(&mut re, &mut im, algorithm);
+    interleave(&re, &im, data);
+    Ok(())
+}
+
+/// `fft_interleaved` for f32 buffers; the transform itself runs in f64.
+///
+/// # Errors
+///
+/// As `fft_interleaved`.
+#[wasm_bindgen]
+pub fn fft_interleaved_f32(data: &mut [f32], algorithm: FftAlgorithm) -> Result<(), JsError> {
+    let n = check_interleaved(data.len(), "fft_interleaved_f32")?;
+    require_pow2(n, "fft_interleaved_f32")?;
+    let (mut re, mut im): (Vec<f64>, Vec<f64>) = data
+        .chunks_exact(2)
+        .map(|pair| (f64::from(pair[0]), f64::from(pair[1])))
+        .unzip();
+    fft_pow2(&mut re, &mut im, algorithm);
+    for ((pair, &r), &i) in data.chunks_exact_mut(2).zip(&re).zip(&im) {
+        pair[0] = f64_to_f32(r);
+        pair[1] = f64_to_f32(i);
+    }
+    Ok(())
+}
+
+/// In-place inverse of `fft_complex`, scaled by `1 / n` so that a forward
+/// transform followed by this one returns the original buffers.
+///
+/// # Errors
+///
+/// As `fft_complex`.
+#[wasm_bindgen]
+pub fn ifft_complex(
+    re: &mut [f64],
+    im: &mut [f64],
+    algorithm: FftAlgorithm,
+) -> Result<(), JsError> {
+    let n = check_split_complex(re.len(), im.len(), "ifft_complex")?;
+    require_pow2(n, "ifft_complex")?;
+    inverse_via_forward(re, im, |re, im| fft_pow2(re, im, algorithm));
+    Ok(())
+}
+
+/// In-place inverse of `fft_interleaved`, scaled by `1 / n`.
+///
+/// # Errors
+///
+/// As `fft_interleaved`.
+#[wasm_bindgen]
+pub fn ifft_interleaved(data: &mut [f64], algorithm: FftAlgorithm) -> Result<(), JsError> {
+    let n = check_interleaved(data.len(), "ifft_interleaved")?;
+    require_pow2(n, "ifft_interleaved")?;
+    let (mut re, mut im) = deinterleave(data);
+    inverse_via_forward(&mut re, &mut im, |re, im| fft_pow2(re, im, algorithm));
+    interleave(&re, &im, data);
+    Ok(())
+}
+
+/// Number of bins in the Hermitian half spectrum of a real signal of length `n`.
+fn half_spectrum_len(n: usize) -> usize {
+    n / 2 + 1
+}
+
+/// Forward FFT of a real signal of any length `n`. Writes the non-negative
+/// frequency bins `X[0..=n/2]` (unnormalized, like `fft_complex`) to
+/// `out_re` / `out_im`; the rest of the spectrum is `X[n - k] = conj(X[k])`.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `out_re` or `out_im` has fewer than `n / 2 + 1` elements, and
+/// `SizeOverflow` if no FFT plan fits for `n`.
+#[wasm_bindgen]
+pub fn rfft(input: &[f64], out_re: &mut [f64], out_im: &mut [f64]) -> Result<(), JsError> {
+    let n = input.len();
+    if n == 0 {
+        return Ok(());
+    }
+    let bins = half_spectrum_len(n);
+    require_len(out_re.len(), bins, "rfft: out_re")?;
+    require_len(out_im.len(), bins, "rfft: out_im")?;
+    rfft_impl(input, &mut out_re[..bins], &mut out_im[..bins])?;
+    Ok(())
+}
+
+/// `rfft` writing the half spectrum as interleaved `[re0, im0, re1, im1, ...]`
+/// (`2 * (n / 2 + 1)` values).
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `out` has fewer than `2 * (n / 2 + 1)` elements, and
+/// `SizeOverflow` if no FFT plan fits for `n`.
+#[wasm_bindgen]
+pub fn rfft_interleaved(input: &[f64], out: &mut [f64]) -> Result<(), JsError> {
+    let n = input.len();
+    if n == 0 {
+        return Ok(());
+    }
+    let bins = half_spectrum_len(n);
+    require_len(out.len(), 2 * bins, "rfft_interleaved: out")?;
+    let mut re = vec![0.0; bins];
+    let mut im = vec![0.0; bins];
+    rfft_impl(input, &mut re, &mut im)?;
+    interleave(&re, &im, &mut out[..2 * bins]);
+    Ok(())
+}
+
+/// Inverse of `rfft`: rebuilds the real signal of length `output.len()` from its
+/// half spectrum, scaled by `1 / n`. The imaginary parts of the DC bin (and of
+/// the Nyquist bin for even `n`) are ignored, as they are zero for real signals.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `re` or `im` has fewer than `n / 2 + 1` elements, and
+/// `SizeOverflow` if no FFT plan fits for `n`.
+#[wasm_bindgen]
+pub fn irfft(re: &[f64], im: &[f64], output: &mut [f64]) -> Result<(), JsError> {
+    let n = output.len();
+    if n == 0 {
+        return Ok(());
+    }
+    let bins = half_spectrum_len(n);
+    require_len(re.len(), bins, "irfft: re")?;
+    require_len(im.len(), bins, "irfft: im")?;
+    irfft_impl(&re[..bins], &im[..bins], output)?;
+    Ok(())
+}
+
+/// `irfft` reading an interleaved half spectrum.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `data` has fewer than `2 * (n / 2 + 1)` elements, and
+/// `SizeOverflow` if no FFT plan fits for `n`.
+#[wasm_bindgen]
+pub fn irfft_interleaved(data: &[f64], output: &mut [f64]) -> Result<(), JsError> {
+    let n = output.len();
+    if n == 0 {
+        return Ok(());
+    }
+    let bins = half_spectrum_len(n);
+    require_len(data.len(), 2 * bins, "irfft_interleaved: data")?;
+    let (re, im) = deinterleave(&data[..2 * bins]);
+    irfft_impl(&re, &im, output)?;
+    Ok(())
+}
+
+/// Real FFT of `input` into `bins = n / 2 + 1` output bins. Even lengths run a
+/// half-length complex FFT over `z[m] = x[2m] + i x[2m+1]` and split the result.
+fn rfft_impl(input: &[f64], out_re: &mut [f64], out_im: &mut [f64]) -> Result<(), WasmError> {
+    let n = input.len();
+    if !n.is_multiple_of(2) {
+        let mut re = input.to_vec();
+        let mut im = vec![0.0; n];
+        kernel_for(n)?.forward(&mut re, &mut im);
+        out_re.copy_from_slice(&re[..out_re.len()]);
+        out_im.copy_from_slice(&im[..out_im.len()]);
+        return Ok(());
+    }
+
+    let half = n / 2;
+    let (mut z_re, mut z_im) = deinterleave(input);
+    kernel_for(half)?.forward(&mut z_re, &mut z_im);
+    let twiddles = twiddles_for(n);
+    for k in 0..=half {
+        let (ar, ai) = (z_re[k % half], z_im[k % half]);
+        let (br, bi) = (z_re[(half - k) % half], -z_im[(half - k) % half]);
+        // Even part (a + b) / 2 and odd part (a - b) / 2i of the packed transform.
+        let (er, ei) = (0.5 * (ar + br), 0.5 * (ai + bi));
+        let (or, oi) = (0.5 * (ai - bi), -0.5 * (ar - br));
+        let (wr, wi) = twiddles.get(k);
+        out_re[k] = er + wr * or - wi * oi;
+        out_im[k] = ei + wr * oi + wi * or;
+    }
+    Ok(())
+}
+
+/// Inverse of `rfft_impl` for an output of length `output.len()`.
+fn irfft_impl(re: &[f64], im: &[f64], output: &mut [f64]) -> Result<(), WasmError> {
+    let n = output.len();
+    if !n.is_multiple_of(2) {
+        let mut full_re = vec![0.0; n];
+        let mut full_im = vec![0.0; n];
+        full_re[0] = re[0];
+        for k in 1..re.len() {
+            full_re[k] = re[k];
+            full_im[k] = im[k];
+            full_re[n - k] = re[k];
+            full_im[n - k] = -im[k];
+        }
+        kernel_for(n)?.inverse(&mut full_re, &mut full_im);
+        output.copy_from_slice(&full_re);
+        return Ok(());
+    }
+
+    let half = n / 2;
+    let twiddles = twiddles_for(n);
+    let mut z_re = vec![0.0; half];
+    let mut z_im = vec![0.0; half];
+    for k in 0..half {
+        let (ar, ai) = (re[k], if k == 0 { 0.0 } else { im[k] });
+        let j = half - k;
+        let (br, bi) = (re[j], if j == half { 0.0 } else { -im[j] });
+        let (er, ei) = (0.5 * (ar + br), 0.5 * (ai + bi));
+        // Odd part: (a - b) / 2 * w^{-k}.
+        let (dr, di) = (0.5 * (ar - br), 0.5 * (ai - bi));
+        let (wr, wi) = twiddles.get(k);
+        let (or, oi) = (dr * wr + di * wi, di * wr - dr * wi);
+        z_re[k] = er - oi;
+        z_im[k] = ei + or;
+    }
+    kernel_for(half)?.inverse(&mut z_re, &mut z_im);
+    interleave(&z_re, &z_im, output);
+    Ok(())
+}
+
+fn store_f32(src: &[f64], dst: &mut [f32]) {
+    for (out, &value) in dst.iter_mut().zip(src) {
+        *out = f64_to_f32(value);
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn f64_to_f32(value: f64) -> f32 {
+    value as f32
+}
+
+fn spectrum_value(re: f64, im: f64, kind: SpectrumKind) -> f64 {
+    match kind {
+        SpectrumKind::Magnitude => re.hypot(im),
+        SpectrumKind::Phase => im.atan2(re),
+        SpectrumKind::Power => re * re + im * im,
+    }
+}
+
+/// Writes magnitude, phase or power of each bin of a split complex spectrum into `out`.
+///
+/// # Errors
+///
+/// `LengthMismatch` if `re` and `im` differ in length, and `BufferTooSmall` if `out`
+/// has fewer elements than `re`.
+#[wasm_bindgen]
+pub fn fft_spectrum(
+    re: &[f64],
+    im: &[f64],
+    out: &mut [f64],
+    kind: SpectrumKind,
+) -> Result<(), JsError> {
+    let n = check_split_complex(re.len(), im.len(), "fft_spectrum")?;
+    require_len(out.len(), n, "fft_spectrum: out")?;
+    for ((value, &r), &i) in out.iter_mut().zip(re).zip(im) {
+        *value = spectrum_value(r, i, kind);
+    }
+    Ok(())
+}
+
+/// `fft_spectrum` for an interleaved `[re0, im0, re1, im1, ...]` spectrum.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `data` has odd length, and `BufferTooSmall` if `out` has
+/// fewer than `data.len() / 2` elements.
+#[wasm_bindgen]
+pub fn fft_spectrum_interleaved(
+    data: &[f64],
+    out: &mut [f64],
+    kind: SpectrumKind,
+) -> Result<(), JsError> {
+    let n = check_interleaved(data.len(), "fft_spectrum_interleaved")?;
+    require_len(out.len(), n, "fft_spectrum_interleaved: out")?;
+    for (value, pair) in out.iter_mut().zip(data.chunks_exact(2)) {
+        *value = spectrum_value(pair[0], pair[1], kind);
+    }
+    Ok(())
+}
+
+/// Reusable forward FFT of a fixed, arbitrary length. Power-of-two lengths use
+/// split-radix, 2/3/5-smooth lengths mixed radix, everything else Bluestein.
+/// Plans for the same length share one cached kernel, and each plan keeps its
+/// kernel alive after the cache evicts it.
+#[wasm_bindgen]
+pub struct FftPlan {
+    len: usize,
+    kernel: Rc<FftKernel>,
+}
+
+#[wasm_bindgen]
+impl FftPlan {
+    /// Builds, or reuses from the cache, the kernel for `len`-point transforms.
+    ///
+    /// # Errors
+    ///
+    /// `SizeOverflow` if the Bluestein work buffer for `len` overflows.
+    #[wasm_bindgen(constructor)]
+    pub fn new(len: usize) -> Result<FftPlan, JsError> {
+        let kernel = kernel_for(len)?;
+        Ok(FftPlan { len, kernel })
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// In-place unnormalized forward FFT of split f64 buffers.
+    ///
+    /// # Errors
+    ///
+    /// `LengthMismatch` if `re` and `im` differ in length or do not match the plan.
+    pub fn forward(&self, re: &mut [f64], im: &mut [f64]) -> Result<(), JsError> {
+        let n = check_split_complex(re.len(), im.len(), "FftPlan.forward")?;
+        self.check_len(n, "FftPlan.forward")?;
+        self.kernel.forward(re, im);
+        Ok(())
+    }
+
+    /// In-place unnormalized forward FFT of an interleaved f64 buffer.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidDimensions` if `data` has odd length, and `LengthMismatch` if its
+    /// complex length does not match the plan.
+    pub fn forward_interleaved(&self, data: &mut [f64]) -> Result<(), JsError> {
+        let n = check_interleaved(data.len(), "FftPlan.forward_interleaved")?;
+        self.check_len(n, "FftPlan.forward_interleaved")?;
+        let (mut re, mut im) = deinterleave(data);
+        self.kernel.forward(&mut re, &mut im);
+        interleave(&re, &im, data);
+        Ok(())
+    }
+
+    /// In-place inverse FFT of split f64 buffers, scaled by `1 / len`.
+    ///
+    /// # Errors
+    ///
+    /// As `forward`.
+    pub fn inverse(&self, re: &mut [f64], im: &mut [f64]) -> Result<(), JsError> {
+        let n = check_split_complex(re.len(), im.len(), "FftPlan.inverse")?;
+        self.check_len(n, "FftPlan.inverse")?;
+        self.kernel.inverse(re, im);
+        Ok(())
+    }
+
+    /// In-place inverse FFT of an interleaved f64 buffer, scaled by `1 / len`.
+    ///
+    /// # Errors
+    ///
+    /// As `forward_interleaved`.
+    pub fn inverse_interleaved(&self, data: &mut [f64]) -> Result<(), JsError> {
+        let n = check_interleaved(data.len(), "FftPlan.inverse_interleaved")?;
+        self.check_len(n, "FftPlan.inverse_interleaved")?;
+        let (mut re, mut im) = deinterleave(data);
+        self.kernel.inverse(&mut re, &mut im);
+        interleave(&re, &im, data);
+        Ok(())
+    }
+}
+
+impl FftPlan {
+    fn check_len(&self, n: usize, what: &str) -> Result<(), WasmError> {
+        if n != self.len {
+            return Err(WasmError::new(
+                ErrorCode::LengthMismatch,
+                format!(
+                    "{what}: buffer has {n} complex elements but the plan is for {}",
+                    self.len
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Magnitude spectrum `|X[k]|` of a real signal, any length.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `output` is shorter than `input`, and `SizeOverflow` if no FFT
+/// plan fits for `input.len()`.
+#[wasm_bindgen]
+pub fn fft_demo(input: &[f64], output: &mut [f64]) -> Result<(), JsError> {
+    require_len(output.len(), input.len(), "fft_demo: output")?;
+    let kernel = kernel_for(input.len())?;
+    let _ = fft_demo_impl(input, output, &kernel, None);
+    Ok(())
+}
+
+/// Same as `fft_demo`, reporting progress in output bins to the control block at
+/// `control_ptr` (see `alloc_control`) and skipping the transform when cancelled.
+/// Cancellation is coarse: the flag is read before the transform and once it has
+/// finished, never between butterfly passes, so `progress` jumps from 0 to `total`.
+///
+/// # Errors
+///
+/// As `fft_demo`, plus `InvalidArgument` if `control_ptr` is not 4-byte aligned.
+#[wasm_bindgen]
+pub fn fft_demo_cancellable(
+    input: &[f64],
+    output: &mut [f64],
+    control_ptr: *const i32,
+) -> Result<KernelStatus, JsError> {
+    require_len(output.len(), input.len(), "fft_demo_cancellable: output")?;
+    let control = control_from_ptr(control_ptr)?;
+    let kernel = kernel_for(input.len())?;
+    Ok(kernel_status(fft_demo_impl(
+        input, output, &kernel, control,
+    )))
+}
+
+fn fft_demo_impl(
+    input: &[f64],
+    output: &mut [f64],
+    kernel: &FftKernel,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
+    let n = input.len().min(output.len());
+    if n == 0 {
+        return Ok(());
+    }
+    if let Some(control) = control {
+        control.begin(n);
+    }
+
+    checkpoint(control, 0)?;
+    let mut re = input[..n].to_vec();
+    let mut im = vec![0.0; n];
+    kernel.forward(&mut re, &mut im);
+    for ((out, &r), &i) in output.iter_mut().zip(&re).zip(&im) {
+        *out = r.hypot(i);
+    }
+    checkpoint(control, n)
+}
+
+/// Three sines at amplitudes 1, 0.5 and 0.3, treating the buffer as one second
+/// (`sample_rate = buffer.len()`). See `SignalGenerator` for anything else.
+///
+/// # Errors
+///
+/// `InvalidArgument` if any frequency is not finite.
+#[wasm_bindgen]
+pub fn generate_signal(
+    buffer: &mut [f64],
+    freq1: f64,
+    freq2: f64,
+    freq3: f64,
+) -> Result<(), JsError> {
+    require_finite(freq1, "generate_signal: freq1")?;
+    require_finite(freq2, "generate_signal: freq2")?;
+    require_finite(freq3, "generate_signal: freq3")?;
+    let n = buffer.len();
+    if n == 0 {
+        return Ok(());
+    }
+
+    let mut generator = SignalGenerator::new(usize_to_f64(n))?;
+    generator.add_sine(freq1, 1.0, 0.0)?;
+    generator.add_sine(freq2, 0.5, 0.0)?;
+    generator.add_sine(freq3, 0.3, 0.0)?;
+    generator.render(buffer);
+    Ok(())
+}
+
+/// DTMF keypad, row-major: row tones select the row, column tones the column.
+const DTMF_KEYS: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+const DTMF_ROWS: [f64; 4] = [697.0, 770.0, 852.0, 941.0];
+const DTMF_COLUMNS: [f64; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+/// Decoder analysis block and hop, in seconds (205 / 102 samples at 8 kHz).
+const DTMF_BLOCK_SECONDS: f64 = 0.0256;
+const DTMF_HOP_SECONDS: f64 = 0.0128;
+/// Share of the block power the two tones must carry together.
+const DTMF_MIN_TONE_SHARE: f64 = 0.6;
+/// The winning tone must beat the runner-up of its group by this power ratio.
+const DTMF_MIN_DOMINANCE: f64 = 4.0;
+/// Largest allowed level difference between the row and column tones.
+const DTMF_MAX_TWIST_DB: f64 = 8.0;
+/// Blocks quieter than this mean power (-80 dBFS) are silence.
+const DTMF_MIN_POWER: f64 = 1e-8;
+
+/// `|X(omega)|^2` of `signal` at `omega` rad/sample (generalized Goertzel).
+fn goertzel_power(signal: &[f64], omega: f64) -> f64 {
+    let coeff = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for &x in signal {
+        let s0 = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0)
+}
+
+/// Single-bin DFT power `|X(f)|^2` of `signal` at each of `frequencies` (Hz),
+/// on the same scale as `SpectrumKind::Power`. Frequencies need not sit on FFT
+/// bins but must lie in `(0, sample_rate / 2)`.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `power` is shorter than `frequencies`, and `InvalidArgument` if
+/// `sample_rate` is not positive or a frequency lies outside `(0, sample_rate / 2)`.
+#[wasm_bindgen]
+pub fn goertzel(
+    signal: &[f64],
+    sample_rate: f64,
+    frequencies: &[f64],
+    power: &mut [f64],
+) -> Result<(), JsError> {
+    require_len(power.len(), frequencies.len(), "goertzel: power")?;
+    for (out, &frequency) in power.iter_mut().zip(frequencies) {
+        let omega = normalized_frequency(sample_rate, frequency, "goertzel")?;
+        *out = goertzel_power(signal, omega);
+    }
+    Ok(())
+}
+
+fn dtmf_tones(digit: char) -> Option<(f64, f64)> {
+    let digit = digit.to_ascii_uppercase();
+    DTMF_KEYS.iter().enumerate().find_map(|(row, keys)| {
+        let column = keys.iter().position(|&key| key == digit)?;
+        Some((DTMF_ROWS[row], DTMF_COLUMNS[column]))
+    })
+}
+
+/// Writes `digits` (`0-9`, `A-D`, `*`, `#`) as DTMF: each digit is
+/// `tone_seconds` of its row and column sines at amplitude 0.5, followed by
+/// `gap_seconds` of silence. Returns the number of samples written; the rest
+/// of `buffer` is zeroed.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not finite and positive, a duration is not
+/// finite, the tone is shorter than one sample, `gap_seconds` is negative, or `digits`
+/// holds a non-DTMF character. `SizeOverflow` if the total length overflows, and
+/// `BufferTooSmall` if `buffer` cannot hold it.
+#[wasm_bindgen]
+pub fn generate_dtmf(
+    buffer: &mut [f64],
+    sample_rate: f64,
+    digits: &str,
+    tone_seconds: f64,
+    gap_seconds: f64,
+) -> Result<usize, JsError> {
+    require_finite(tone_seconds, "generate_dtmf: tone_seconds")?;
+    require_finite(gap_seconds, "generate_dtmf: gap_seconds")?;
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("generate_dtmf: sample rate must be positive, got {sample_rate}"),
+        )
+        .into());
+    }
+    let tone_len = f64_to_usize((tone_seconds * sample_rate).round());
+    let gap_len = f64_to_usize((gap_seconds * sample_rate).round());
+    if tone_len == 0 || gap_seconds < 0.0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "generate_dtmf: tones must last at least one sample and gaps must not be \
+                 negative (got {tone_seconds} s and {gap_seconds} s)"
+            ),
+        )
+        .into());
+    }
+    let tones = digits
+        .chars()
+        .map(|digit| {
+            dtmf_tones(digit).ok_or_else(|| {
+                WasmError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("generate_dtmf: '{digit}' is not a DTMF digit"),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let total = tone_len
+        .checked_add(gap_len)
+        .and_then(|step| step.checked_mul(tones.len()))
+        .ok_or_else(|| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                "generate_dtmf: tone and gap lengths overflow the buffer size",
+            )
+        })?;
+    require_len(buffer.len(), total, "generate_dtmf: buffer")?;
+
+    buffer.fill(0.0);
+    for (chunk, (row, column)) in buffer.chunks_mut(tone_len + gap_len).zip(tones) {
+        let mut generator = SignalGenerator::new(sample_rate)?;
+        generator.add_sine(row, 0.5, 0.0)?;
+        generator.add_sine(column, 0.5, 0.0)?;
+        generator.render(&mut chunk[..tone_len]);
+    }
+    Ok(total)
+}
+
+/// Index of the strongest entry and whether it dominates the others.
+fn dominant_tone(powers: &[f64; 4]) -> (usize, bool) {
+    let mut best = 0;
+    for (i, &power) in powers.iter().enumerate() {
+        if power > powers[best] {
+            best = i;
+        }
+    }
+    let dominant = powers
+        .iter()
+        .enumerate()
+        .all(|(i, &power)| i == best || power * DTMF_MIN_DOMINANCE < powers[best]);
+    (best, dominant)
+}
+
+/// Digit carried by one analysis block, if any. `rows` and `columns` hold the
+/// tone frequencies in rad/sample.
+fn dtmf_block(block: &[f64], rows: &[f64; 4], columns: &[f64; 4]) -> Option<char> {
+    let len = usize_to_f64(block.len());
+    let mean_power = block.iter().map(|x| x * x).sum::<f64>() / len;
+    if mean_power < DTMF_MIN_POWER {
+        return None;
+    }
+    // 2|X|^2 / N^2 is the mean power of a sine at exactly that frequency.
+    let tone_power = |omega: f64| 2.0 * goertzel_power(block, omega) / (len * len);
+    let row_powers = rows.map(tone_power);
+    let column_powers = columns.map(tone_power);
+    let (row, row_dominant) = dominant_tone(&row_powers);
+    let (column, column_dominant) = dominant_tone(&column_powers);
+    let (row_power, column_power) = (row_powers[row], column_powers[column]);
+
+    let twist_db = 10.0 * (row_power / column_power).log10();
+    let valid = row_dominant
+        && column_dominant
+        && row_power + column_power >= DTMF_MIN_TONE_SHARE * mean_power
+        && twist_db.abs() <= DTMF_MAX_TWIST_DB;
+    valid.then_some(DTMF_KEYS[row][column])
+}
+
+/// Decodes DTMF digits from `signal`. Tones and the gaps between repeated
+/// digits should each last at least 40 ms (ITU-T Q.24); a digit is reported
+/// once per tone burst. `sample_rate` must exceed 3266 Hz to resolve the
+/// highest column tone.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not finite or is too low to resolve every DTMF
+/// tone.
+#[wasm_bindgen]
+pub fn decode_dtmf(signal: &[f64], sample_rate: f64) -> Result<String, JsError> {
+    let mut rows = [0.0; 4];
+    let mut columns = [0.0; 4];
+    for (omega, &frequency) in rows.iter_mut().zip(&DTMF_ROWS) {
+        *omega = normalized_frequency(sample_rate, frequency, "decode_dtmf")?;
+    }
+    for (omega, &frequency) in columns.iter_mut().zip(&DTMF_COLUMNS) {
+        *omega = normalized_frequency(sample_rate, frequency, "decode_dtmf")?;
+    }
+    let block_len = f64_to_usize((DTMF_BLOCK_SECONDS * sample_rate).round());
+    let hop = f64_to_usize((DTMF_HOP_SECONDS * sample_rate).round()).max(1);
+
+    let mut digits = String::new();
+    let mut previous = None;
+    let mut start = 0;
+    while start + block_len <= signal.len() {
+        let current = dtmf_block(&signal[start..start + block_len], &rows, &columns);
+        if let Some(digit) = current.filter(|&digit| previous != Some(digit)) {
+            digits.push(digit);
+        }
+        previous = current;
+        start += hop;
+    }
+    Ok(digits)
+}
+
+/// Periodic waveform shapes for `SignalGenerator::add_wave`. All are aligned
+/// with the sine: zero phase starts a rising half cycle.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine = 0,
+    Square = 1,
+    Sawtooth = 2,
+    Triangle = 3,
+}
+
+/// Spectral color of `SignalGenerator` noise.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseColor {
+    /// Gaussian, flat spectrum.
+    White = 0,
+    /// -3 dB/octave (Paul Kellet's filter).
+    Pink = 1,
+    /// -6 dB/octave (leaky integrator).
+    Brown = 2,
+}
+
+/// Frequency law of a `SignalGenerator` chirp.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChirpKind {
+    Linear = 0,
+    /// Exponential sweep; both frequencies must be positive.
+    Logarithmic = 1,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SignalComponent {
+    Wave {
+        shape: Waveform,
+        frequency: f64,
+        amplitude: f64,
+        phase: f64,
+    },
+    Pulse {
+        frequency: f64,
+        amplitude: f64,
+        phase: f64,
+        duty: f64,
+    },
+    Noise {
+        color: NoiseColor,
+        amplitude: f64,
+        seed: u32,
+    },
+    Chirp {
+        kind: ChirpKind,
+        f0: f64,
+        f1: f64,
+        duration: f64,
+        amplitude: f64,
+        phase: f64,
+    },
+}
+
+/// Sum of periodic waves, noise and chirps at an explicit sample rate plus a
+/// DC offset. Frequencies are in Hz, phases in radians and sample `i` is at
+/// `t = i / sample_rate` seconds. Rendering is deterministic: noise restarts
+/// from its seed on every `render`.
+#[wasm_bindgen]
+pub struct SignalGenerator {
+    sample_rate: f64,
+    dc_offset: f64,
+    components: Vec<SignalComponent>,
+}
+
+#[wasm_bindgen]
+impl SignalGenerator {
+    /// Empty generator rendering at `sample_rate` Hz.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `sample_rate` is not finite and positive.
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f64) -> Result<SignalGenerator, JsError> {
+        if !(sample_rate.is_finite() && sample_rate > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("SignalGenerator: sample rate must be positive, got {sample_rate}"),
+            )
+            .into());
+        }
+        Ok(SignalGenerator {
+            sample_rate,
+            dc_offset: 0.0,
+            components: Vec::new(),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn dc_offset(&self) -> f64 {
+        self.dc_offset
+    }
+
+    /// Constant added to every rendered sample.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `offset` is not finite.
+    #[wasm_bindgen(setter)]
+    pub fn set_dc_offset(&mut self, offset: f64) -> Result<(), JsError> {
+        require_finite(offset, "SignalGenerator: dc_offset")?;
+        self.dc_offset = offset;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Removes every component; the DC offset is kept.
+    pub fn clear(&mut self) {
+        self.components.clear();
+    }
+
+    /// Adds `amplitude * sin(2 pi frequency t + phase)`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `frequency`, `amplitude` or `phase` is not finite.
+    pub fn add_sine(&mut self, frequency: f64, amplitude: f64, phase: f64) -> Result<(), JsError> {
+        self.add_wave(Waveform::Sine, frequency, amplitude, phase)
+    }
+
+    /// Adds a periodic wave of the given `shape`, starting at `phase`.
+    ///
+    /// # Errors
+    ///
+    /// As `add_sine`.
+    pub fn add_wave(
+        &mut self,
+        shape: Waveform,
+        frequency: f64,
+        amplitude: f64,
+        phase: f64,
+    ) -> Result<(), JsError> {
+        check_tone(frequency, amplitude, phase, "SignalGenerator.add_wave")?;
+        self.components.push(SignalComponent::Wave {
+            shape,
+            frequency,
+            amplitude,
+            phase,
+        });
+        Ok(())
+    }
+
+    /// Rectangular wave that is `+amplitude` for the first `duty` fraction of
+    /// each cycle and `-amplitude` for the rest.
+    ///
+    /// # Errors
+    ///
+    /// As `add_sine`, plus `InvalidArgument` if `duty` is not strictly between 0 and 1.
+    pub fn add_pulse(
+        &mut self,
+        frequency: f64,
+        amplitude: f64,
+        phase: f64,
+        duty: f64,
+    ) -> Result<(), JsError> {
+        check_tone(frequency, amplitude, phase, "SignalGenerator.add_pulse")?;
+        if !(duty > 0.0 && duty < 1.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("SignalGenerator.add_pulse: duty must be in (0, 1), got {duty}"),
+            )
+            .into());
+        }
+        self.components.push(SignalComponent::Pulse {
+            frequency,
+            amplitude,
+            phase,
+            duty,
+        });
+        Ok(())
+    }
+
+    /// Zero-mean noise with RMS `amplitude` over each rendered buffer.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `amplitude` is not finite.
+    pub fn add_noise(
+        &mut self,
+        color: NoiseColor,
+        amplitude: f64,
+        seed: u32,
+    ) -> Result<(), JsError> {
+        require_finite(amplitude, "SignalGenerator.add_noise: amplitude")?;
+        self.components.push(SignalComponent::Noise {
+            color,
+            amplitude,
+            seed,
+        });
+        Ok(())
+    }
+
+    /// Sweep from `f0` Hz at `t = 0` to `f1` Hz at `t = duration` seconds; the
+    /// same law continues past `duration`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if an argument is not finite, `duration` is not positive, or a
+    /// logarithmic chirp has a non-positive frequency.
+    pub fn add_chirp(
+        &mut self,
+        kind: ChirpKind,
+        f0: f64,
+        f1: f64,
+        duration: f64,
+        amplitude: f64,
+        phase: f64,
+    ) -> Result<(), JsError> {
+        let what = "SignalGenerator.add_chirp";
+        check_tone(f0, amplitude, phase, what)?;
+        require_finite(f1, what)?;
+        if !(duration.is_finite() && duration > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: duration must be positive, got {duration}"),
+            )
+            .into());
+        }
+        if kind == ChirpKind::Logarithmic && !(f0 > 0.0 && f1 > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: logarithmic chirp needs positive frequencies, got {f0} and {f1}"),
+            )
+            .into());
+        }
+        self.components.push(SignalComponent::Chirp {
+            kind,
+            f0,
+            f1,
+            duration,
+            amplitude,
+            phase,
+        });
+        Ok(())
+    }
+
+    /// Overwrites `buffer` with the DC offset plus every component.
+    pub fn render(&self, buffer: &mut [f64]) {
+        buffer.fill(self.dc_offset);
+        for component in &self.components {
+            self.render_component(component, buffer);
+        }
+    }
+
+    /// `render` into an f32 buffer.
+    pub fn render_f32(&self, buffer: &mut [f32]) {
+        let mut samples = vec![0.0; buffer.len()];
+        self.render(&mut samples);
+        store_f32(&samples, buffer);
+    }
+}
+
+impl SignalGenerator {
+    fn time(&self, i: usize) -> f64 {
+        usize_to_f64(i) / self.sample_rate
+    }
+
+    fn render_component(&self, component: &SignalComponent, buffer: &mut [f64]) {
+        use std::f64::consts::PI;
+        match *component {
+            SignalComponent::Wave {
+                shape,
+                frequency,
+                amplitude,
+                phase,
+            } => {
+                for (i, sample) in buffer.iter_mut().enumerate() {
+                    let angle = 2.0 * PI * frequency * self.time(i) + phase;
+                    *sample += amplitude * waveform_value(shape, angle);
+                }
+            }
+            SignalComponent::Pulse {
+                frequency,
+                amplitude,
+                phase,
+                duty,
+            } => {
+                for (i, sample) in buffer.iter_mut().enumerate() {
+                    let cycle = cycle_position(2.0 * PI * frequency * self.time(i) + phase);
+                    *sample += if cycle < duty { amplitude } else { -amplitude };
+                }
+            }
+            SignalComponent::Noise {
+                color,
+                amplitude,
+                seed,
+            } => {
+                let noise = colored_noise(color, seed, buffer.len());
+                let rms = (noise.iter().map(|v| v * v).sum::<f64>()
+                    / usize_to_f64(noise.len().max(1)))
+                .sqrt();
+                let scale = if rms > 0.0 { amplitude / rms } else { 0.0 };
+                for (sample, value) in buffer.iter_mut().zip(noise) {
+                    *sample += scale * value;
+                }
+            }
+            SignalComponent::Chirp {
+                kind,
+                f0,
+                f1,
+                duration,
+                amplitude,
+                phase,
+            } => {
+                for (i, sample) in buffer.iter_mut().enumerate() {
+                    let t = self.time(i);
+                    let cycles = match kind {
+                        ChirpKind::Linear => f0 * t + (f1 - f0) * t * t / (2.0 * duration),
+                        ChirpKind::Logarithmic => {
+                            let growth = (f1 / f0).ln() / duration;
+                            if growth.abs() < 1e-12 {
+                                f0 * t
+                            } else {
+                                f0 * ((growth * t).exp() - 1.0) / growth
+                            }
+                        }
+                    };
+                    *sample += amplitude * (2.0 * PI * cycles + phase).sin();
+                }
+            }
+        }
+    }
+}
+
+fn check_tone(frequency: f64, amplitude: f64, phase: f64, what: &str) -> Result<(), WasmError> {
+    require_finite(frequency, what)?;
+    require_finite(amplitude, what)?;
+    require_finite(phase, what)
+}
+
+/// Position within the cycle of phase angle `angle`, in `[0, 1)`.
+fn cycle_position(angle: f64) -> f64 {
+    let cycles = angle / (2.0 * std::f64::consts::PI);
+    cycles - cycles.floor()
+}
+
+fn waveform_value(shape: Waveform, angle: f64) -> f64 {
+    match shape {
+        Waveform::Sine => angle.sin(),
+        Waveform::Square => {
+            if cycle_position(angle) < 0.5 {
+                1.0
+            } else {
+                -1.0
+            }
+        }
+        Waveform::Sawtooth => {
+            let shifted = cycle_position(angle) + 0.5;
+            2.0 * (shifted - shifted.floor()) - 1.0
+        }
+        Waveform::Triangle => {
+            let shifted = cycle_position(angle) + 0.25;
+            1.0 - 4.0 * (shifted - shifted.floor() - 0.5).abs()
+        }
+    }
+}
+
+/// `SplitMix64`: small, seedable and good enough for test signals.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `(0, 1]`.
+    fn next_unit(&mut self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let mantissa = (self.next_u64() >> 11) as f64;
+        (mantissa + 1.0) / 9_007_199_254_740_992.0
+    }
+
+    /// Standard normal sample (Box-Muller).
+    fn next_gaussian(&mut self) -> f64 {
+        let radius = (-2.0 * self.next_unit().ln()).sqrt();
+        radius * (2.0 * std::f64::consts::PI * self.next_unit()).cos()
+    }
+}
+
+/// Unscaled noise of the given color. Pink and brown noise have their mean
+/// removed, since their low-frequency content would otherwise show up as DC.
+fn colored_noise(color: NoiseColor, seed: u32, len: usize) -> Vec<f64> {
+    let mut rng = SplitMix64(u64::from(seed));
+    let mut noise: Vec<f64> = match color {
+        NoiseColor::White => (0..len).map(|_| rng.next_gaussian()).collect(),
+        NoiseColor::Pink => {
+            let mut b = [0.0; 7];
+            (0..len)
+                .map(|_| {
+                    let white = rng.next_gaussian();
+                    b[0] = 0.998_86 * b[0] + white * 0.055_517_9;
+                    b[1] = 0.993_32 * b[1] + white * 0.075_075_9;
+                    b[2] = 0.969_00 * b[2] + white * 0.153_852_0;
+                    b[3] = 0.866_50 * b[3] + white * 0.310_485_6;
+                    b[4] = 0.550_00 * b[4] + white * 0.532_952_2;
+                    b[5] = -0.761_6 * b[5] - white * 0.016_898_0;
+                    let pink = b.iter().sum::<f64>() + white * 0.536_2;
+                    b[6] = white * 0.115_926;
+                    pink
+                })
+                .collect()
+        }
+        NoiseColor::Brown => {
+            let mut level = 0.0;
+            (0..len)
+                .map(|_| {
+                    level = 0.998 * level + rng.next_gaussian();
+                    level
+                })
+                .collect()
+        }
+    };
+    if color != NoiseColor::White && len > 0 {
+        let mean = noise.iter().sum::<f64>() / usize_to_f64(len);
+        for value in &mut noise {
+            *value -= mean;
+        }
+    }
+    noise
+}
+
+fn usize_to_f64(value: usize) -> f64 {
+    let Ok(converted) = u32::try_from(value) else {
+        return f64::from(u32::MAX);
+    };
+    f64::from(converted)
+}
+
+/// Saturating conversion of a non-negative whole number; NaN maps to 0.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn f64_to_usize(value: f64) -> usize {
+    value.clamp(0.0, f64::from(u32::MAX)) as usize
+}
+
+// ============================================================================
+// 2D FFT
+// ============================================================================
+
+/// Element count of a `width` x `height` grid, rejecting empty or overflowing sizes.
+fn grid_len(width: usize, height: usize, what: &str) -> Result<usize, WasmError> {
+    if width == 0 || height == 0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: grid is {width}x{height}, both sides must be non-zero"),
+        ));
+    }
+    width.checked_mul(height).ok_or_else(|| {
+        WasmError::new(
+            ErrorCode::SizeOverflow,
+            format!("{what}: {width}x{height} grid overflows"),
+        )
+    })
+}
+
+/// In-place 2D transform of a row-major `width` x `height` split complex grid:
+/// every row, then every column. The inverse is scaled by `1 / (width * height)`.
+fn fft2d_impl(
+    re: &mut [f64],
+    im: &mut [f64],
+    width: usize,
+    height: usize,
+    inverse: bool,
+) -> Result<(), WasmError> {
+    let rows = kernel_for(width)?;
+    for (row_re, row_im) in re.chunks_exact_mut(width).zip(im.chunks_exact_mut(width)) {
+        if inverse {
+            rows.inverse(row_re, row_im);
+        } else {
+            rows.forward(row_re, row_im);
+        }
+    }
+    let columns = kernel_for(height)?;
+    let mut column_re = vec![0.0; height];
+    let mut column_im = vec![0.0; height];
+    for x in 0..width {
+        for y in 0..height {
+            column_re[y] = re[y * width + x];
+            column_im[y] = im[y * width + x];
+        }
+        if inverse {
+            columns.inverse(&mut column_re, &mut column_im);
+        } else {
+            columns.forward(&mut column_re, &mut column_im);
+        }
+        for y in 0..height {
+            re[y * width + x] = column_re[y];
+            im[y * width + x] = column_im[y];
+        }
+    }
+    Ok(())
+}
+
+fn check_grid(
+    re_len: usize,
+    im_len: usize,
+    width: usize,
+    height: usize,
+    what: &str,
+) -> Result<usize, WasmError> {
+    let n = check_split_complex(re_len, im_len, what)?;
+    let len = grid_len(width, height, what)?;
+    if n != len {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!("{what}: buffers hold {n} elements, a {width}x{height} grid needs {len}"),
+        ));
+    }
+    Ok(len)
+}
+
+/// In-place forward 2D FFT of a row-major `width` x `height` split complex
+/// grid (any sizes). Bin `(u, v)` sits at `v * width + u`.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if
+/// `width * height` overflows, and `LengthMismatch` if `re` and `im` do not both hold
+/// `width * height` elements.
+#[wasm_bindgen]
+pub fn fft2d(re: &mut [f64], im: &mut [f64], width: usize, height: usize) -> Result<(), JsError> {
+    check_grid(re.len(), im.len(), width, height, "fft2d")?;
+    fft2d_impl(re, im, width, height, false)?;
+    Ok(())
+}
+
+/// In-place inverse of `fft2d`, scaled by `1 / (width * height)`.
+///
+/// # Errors
+///
+/// As `fft2d`.
+#[wasm_bindgen]
+pub fn ifft2d(re: &mut [f64], im: &mut [f64], width: usize, height: usize) -> Result<(), JsError> {
+    check_grid(re.len(), im.len(), width, height, "ifft2d")?;
+    fft2d_impl(re, im, width, height, true)?;
+    Ok(())
+}
+
+/// Rotates a row-major grid by `(dx, dy)` with wrap-around.
+fn rotate_grid(data: &mut [f64], width: usize, dx: usize, dy: usize) {
+    for row in data.chunks_exact_mut(width) {
+        row.rotate_right(dx);
+    }
+    data.rotate_right(dy * width);
+}
+
+/// Moves the zero-frequency bin of a `width` x `height` grid to the centre
+/// (`(width / 2, height / 2)`), as for display. Undo with `ifftshift2d`.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if
+/// `width * height` overflows, and `BufferTooSmall` if `data` is shorter than that.
+#[wasm_bindgen]
+pub fn fftshift2d(data: &mut [f64], width: usize, height: usize) -> Result<(), JsError> {
+    let len = grid_len(width, height, "fftshift2d")?;
+    require_len(data.len(), len, "fftshift2d: data")?;
+    rotate_grid(&mut data[..len], width, width / 2, height / 2);
+    Ok(())
+}
+
+/// Inverse of `fftshift2d` (they differ for odd sizes).
+///
+/// # Errors
+///
+/// As `fftshift2d`.
+#[wasm_bindgen]
+pub fn ifftshift2d(data: &mut [f64], width: usize, height: usize) -> Result<(), JsError> {
+    let len = grid_len(width, height, "ifftshift2d")?;
+    require_len(data.len(), len, "ifftshift2d: data")?;
+    rotate_grid(
+        &mut data[..len],
+        width,
+        width.div_ceil(2),
+        height.div_ceil(2),
+    );
+    Ok(())
+}
+
+/// Renders the spectrum of an RGBA image's luminance (weights as in
+/// `grayscale`) into `out` as opaque grey RGBA: `ln(1 + |X|)` scaled so the
+/// strongest bin is white, zero frequency at the centre.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `width` or `height` is zero, `SizeOverflow` if the image size
+/// overflows, and `BufferTooSmall` if `data` or `out` holds fewer than
+/// `width * height * 4` bytes.
+#[wasm_bindgen]
+pub fn image_spectrum(data: &[u8], width: u32, height: u32, out: &mut [u8]) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "image_spectrum")?;
+    require_len(data.len(), bytes, "image_spectrum: data")?;
+    require_len(out.len(), bytes, "image_spectrum: out")?;
+
+    let mut re = luminance_plane(&data[..bytes]);
+    let mut im = vec![0.0; re.len()];
+    fft2d_impl(&mut re, &mut im, w, h, false)?;
+    let mut magnitude: Vec<f64> = re
+        .iter()
+        .zip(&im)
+        .map(|(r, i)| r.hypot(*i).ln_1p())
+        .collect();
+    rotate_grid(&mut magnitude, w, w / 2, h / 2);
+
+    let max = magnitude.iter().copied().fold(0.0, f64::max);
+    let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+    for (pixel, &value) in out[..bytes].chunks_exact_mut(4).zip(&magnitude) {
+        let gray = clamp_to_u8(value * scale);
+        pixel.copy_from_slice(&[gray, gray, gray, u8::MAX]);
+    }
+    Ok(())
+}
+
+/// Gaussian low-/high-pass filtering of an RGBA image through the 2D FFT, in
+/// O(n log n) whatever the blur size. `cutoff` is the -3 dB frequency in
+/// cycles per pixel, in `(0, 0.5]`; a lowpass equals a Gaussian blur with
+/// sigma `sqrt(2 ln 2) / (2 pi cutoff)` pixels. Highpass output is offset to
+/// mid-grey (128) so negative detail stays visible. Alpha is left untouched.
+/// Edges are clamp-padded by three sigmas, capped at the image size, so the
+/// transform does not wrap around unless `cutoff` is below about
+/// `0.56 / min(width, height)`; wider blurs mix in the opposite edge.
+///
+/// # Errors
+///
+/// As `image_spectrum` for `data`, plus `InvalidArgument` if `cutoff` is outside
+/// `(0, 0.5]` or `band` is bandpass or bandstop.
+#[wasm_bindgen]
+pub fn frequency_filter(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    band: FilterBand,
+    cutoff: f64,
+) -> Result<(), JsError> {
+    let (w, h, bytes) = image_dims(width, height, "frequency_filter")?;
+    require_len(data.len(), bytes, "frequency_filter: data")?;
+    if !(cutoff > 0.0 && cutoff <= 0.5) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("frequency_filter: cutoff must be in (0, 0.5] cycles/pixel, got {cutoff}"),
+        )
+        .into());
+    }
+    let highpass = match band {
+        FilterBand::Lowpass => false,
+        FilterBand::Highpass => true,
+        FilterBand::Bandpass | FilterBand::Bandstop => {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                "frequency_filter: only lowpass and highpass are supported",
+            )
+            .into())
+        }
+    };
+
+    // Pad by three spatial sigmas (capped at the image size) of clamped edge.
+    let sigma = (2.0 * std::f64::consts::LN_2).sqrt() / (2.0 * std::f64::consts::PI * cutoff);
+    let pad = f64_to_usize((3.0 * sigma).ceil());
+    let (pad_x, pad_y) = (pad.min(w), pad.min(h));
+    let (pw, ph) = (w + 2 * pad_x, h + 2 * pad_y);
+    grid_len(pw, ph, "frequency_filter")?;
+    let source = |x: usize, y: usize, channel: usize| {
+        let sx = x.saturating_sub(pad_x).min(w - 1);
+        let sy = y.saturating_sub(pad_y).min(h - 1);
+        f64::from(data[(sy * w + sx) * 4 + channel])
+    };
+
+    // H(f) = exp(-ln 2 / 2 * (f / cutoff)^2): real and even, so two channels
+    // can share one complex transform as its real and imaginary parts.
+    let response = |k: usize, n: usize| {
+        let f = usize_to_f64(k.min(n - k)) / usize_to_f64(n);
+        f * f
+    };
+    let gain = |u: usize, v: usize| {
+        let lowpass = (-0.5 * std::f64::consts::LN_2 * (response(u, pw) + response(v, ph))
+            / (cutoff * cutoff))
+            .exp();
+        if highpass {
+            1.0 - lowpass
+        } else {
+            lowpass
+        }
+    };
+
+    let mut filtered = vec![[0.0; 3]; w * h];
+    for (first, second) in [(0, Some(1)), (2, None)] {
+        let mut re = vec![0.0; pw * ph];
+        let mut im = vec![0.0; pw * ph];
+        for y in 0..ph {
+            for x in 0..pw {
+                re[y * pw + x] = source(x, y, first);
+                if let Some(second) = second {
+                    im[y * pw + x] = source(x, y, second);
+                }
+            }
+        }
+        fft2d_impl(&mut re, &mut im, pw, ph, false)?;
+        for v in 0..ph {
+            for u in 0..pw {
+                let g = gain(u, v);
+                re[v * pw + u] *= g;
+                im[v * pw + u] *= g;
+            }
+        }
+        fft2d_impl(&mut re, &mut im, pw, ph, true)?;
+        for y in 0..h {
+            for x in 0..w {
+                let index = (y + pad_y) * pw + x + pad_x;
+                filtered[y * w + x][first] = re[index];
+                if let Some(second) = second {
+                    filtered[y * w + x][second] = im[index];
+                }
+            }
+        }
+    }
+
+    let offset = if highpass { 128.0 } else { 0.0 };
+    for (pixel, values) in data[..bytes].chunks_exact_mut(4).zip(&filtered) {
+        for (byte, &value) in pixel.iter_mut().zip(values) {
+            *byte = clamp_to_u8(value + offset);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// WINDOW FUNCTIONS
+// ============================================================================
+
+/// Tapering window applied before an FFT to reduce spectral leakage.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowKind {
+    Rectangular = 0,
+    Hann = 1,
+    Hamming = 2,
+    Blackman = 3,
+    /// 4-term Blackman-Harris (-92 dB side lobes).
+    BlackmanHarris = 4,
+    /// Kaiser-Bessel; `param` is beta, in `[0, 700]`.
+    Kaiser = 5,
+    /// 5-term flat-top, for amplitude readings between bins.
+    FlatTop = 6,
+    /// Tapered cosine; `param` is the taper fraction alpha in `[0, 1]`
+    /// (0 = rectangular, 1 = Hann).
+    Tukey = 7,
+}
+
+const FLAT_TOP_COEFFS: [f64; 5] = [
+    0.215_578_95,
+    0.416_631_58,
+    0.277_263_158,
+    0.083_578_947,
+    0.006_947_368,
+];
+
+/// Largest Kaiser beta accepted: `I0(beta)` overflows f64 just above 713, which
+/// would turn every window value into `inf / inf`.
+const KAISER_MAX_BETA: f64 = 700.0;
+
+fn check_window_param(kind: WindowKind, param: f64, what: &str) -> Result<(), WasmError> {
+    match kind {
+        WindowKind::Kaiser if !(0.0..=KAISER_MAX_BETA).contains(&param) => Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: Kaiser beta must be in [0, {KAISER_MAX_BETA}], got {param}"),
+        )),
+        WindowKind::Tukey if !(0.0..=1.0).contains(&param) => Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: Tukey alpha must be in [0, 1], got {param}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Sum of cosines `sum_j (-1)^j a_j cos(2 pi j x)`.
+fn cosine_sum(coeffs: &[f64], x: f64) -> f64 {
+    coeffs
+        .iter()
+        .enumerate()
+        .map(|(j, &a)| {
+            let sign = if j.is_multiple_of(2) { 1.0 } else { -1.0 };
+            sign * a * (2.0 * std::f64::consts::PI * usize_to_f64(j) * x).cos()
+        })
+        .sum()
+}
+
+/// Modified Bessel function of the first kind, order 0 (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let half_sq = 0.25 * x * x;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-17 {
+        term *= half_sq / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Window value at normalized position `x = i / d` in `[0, 1]`.
+fn window_value(kind: WindowKind, param: f64, x: f64) -> f64 {
+    use std::f64::consts::PI;
+    match kind {
+        WindowKind::Rectangular => 1.0,
+        WindowKind::Hann => cosine_sum(&[0.5, 0.5], x),
+        WindowKind::Hamming => cosine_sum(&[0.54, 0.46], x),
+        WindowKind::Blackman => cosine_sum(&[0.42, 0.5, 0.08], x),
+        WindowKind::BlackmanHarris => cosine_sum(&[0.358_75, 0.488_29, 0.141_28, 0.011_68], x),
+        WindowKind::FlatTop => cosine_sum(&FLAT_TOP_COEFFS, x),
+        WindowKind::Kaiser => {
+            let r = 2.0 * x - 1.0;
+            bessel_i0(param * (1.0 - r * r).max(0.0).sqrt()) / bessel_i0(param)
+        }
+        WindowKind::Tukey => {
+            let alpha = param;
+            if alpha <= 0.0 {
+                1.0
+            } else if x < alpha / 2.0 {
+                0.5 * (1.0 + (PI * (2.0 * x / alpha - 1.0)).cos())
+            } else if x > 1.0 - alpha / 2.0 {
+                0.5 * (1.0 + (PI * (2.0 * (1.0 - x) / alpha - 1.0)).cos())
+            } else {
+                1.0
+            }
+        }
+    }
+}
+
+/// Fills `out` with the window. Periodic windows (denominator `n`) are the
+/// usual choice before an FFT; symmetric ones (denominator `n - 1`) suit FIR design.
+fn fill_window(out: &mut [f64], kind: WindowKind, param: f64, periodic: bool) {
+    let n = out.len();
+    if n == 1 {
+        out[0] = 1.0;
+        return;
+    }
+    let denom = usize_to_f64(if periodic { n } else { n - 1 });
+    for (i, value) in out.iter_mut().enumerate() {
+        *value = window_value(kind, param, usize_to_f64(i) / denom);
+    }
+}
+
+fn window_vec(
+    len: usize,
+    kind: WindowKind,
+    param: f64,
+    periodic: bool,
+    what: &str,
+) -> Result<Vec<f64>, WasmError> {
+    check_window_param(kind, param, what)?;
+    if len == 0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: window length must be positive"),
+        ));
+    }
+    let mut window = vec![0.0; len];
+    fill_window(&mut window, kind, param, periodic);
+    Ok(window)
+}
+
+/// Writes the window of length `out.len()` into `out`. `param` is beta for
+/// Kaiser and alpha for Tukey and is ignored otherwise.
+///
+/// # Errors
+///
+/// `InvalidArgument` if a Kaiser beta is outside `[0, 700]` or a Tukey alpha is
+/// outside `[0, 1]`.
+#[wasm_bindgen]
+pub fn generate_window(
+    out: &mut [f64],
+    kind: WindowKind,
+    param: f64,
+    periodic: bool,
+) -> Result<(), JsError> {
+    check_window_param(kind, param, "generate_window")?;
+    if !out.is_empty() {
+        fill_window(out, kind, param, periodic);
+    }
+    Ok(())
+}
+
+/// Multiplies `data` in place by the window of length `data.len()`.
+///
+/// # Errors
+///
+/// As `generate_window`.
+#[wasm_bindgen]
+pub fn apply_window(
+    data: &mut [f64],
+    kind: WindowKind,
+    param: f64,
+    periodic: bool,
+) -> Result<(), JsError> {
+    check_window_param(kind, param, "apply_window")?;
+    if data.is_empty() {
+        return Ok(());
+    }
+    let window = window_vec(data.len(), kind, param, periodic, "apply_window")?;
+    for (sample, w) in data.iter_mut().zip(window) {
+        *sample *= w;
+    }
+    Ok(())
+}
+
+/// `apply_window` for f32 buffers.
+///
+/// # Errors
+///
+/// As `generate_window`.
+#[wasm_bindgen]
+pub fn apply_window_f32(
+    data: &mut [f32],
+    kind: WindowKind,
+    param: f64,
+    periodic: bool,
+) -> Result<(), JsError> {
+    check_window_param(kind, param, "apply_window_f32")?;
+    if data.is_empty() {
+        return Ok(());
+    }
+    let window = window_vec(data.len(), kind, param, periodic, "apply_window_f32")?;
+    for (sample, w) in data.iter_mut().zip(window) {
+        *sample = f64_to_f32(f64::from(*sample) * w);
+    }
+    Ok(())
+}
+
+/// Coherent gain `sum(w) / n`: the factor a windowed sine's peak amplitude is
+/// scaled by. Divide an amplitude reading by it to correct for the window.
+///
+/// # Errors
+///
+/// As `generate_window`, plus `InvalidDimensions` if `len` is zero.
+#[wasm_bindgen]
+pub fn window_coherent_gain(
+    kind: WindowKind,
+    param: f64,
+    len: usize,
+    periodic: bool,
+) -> Result<f64, JsError> {
+    let window = window_vec(len, kind, param, periodic, "window_coherent_gain")?;
+    Ok(window.iter().sum::<f64>() / usize_to_f64(len))
+}
+
+/// Equivalent noise bandwidth in bins, `n * sum(w^2) / sum(w)^2`. Divide a
+/// power spectrum by it to read noise power density per bin.
+///
+/// # Errors
+///
+/// As `window_coherent_gain`.
+#[wasm_bindgen]
+pub fn window_enbw(
+    kind: WindowKind,
+    param: f64,
+    len: usize,
+    periodic: bool,
+) -> Result<f64, JsError> {
+    let window = window_vec(len, kind, param, periodic, "window_enbw")?;
+    let sum: f64 = window.iter().sum();
+    let sum_sq: f64 = window.iter().map(|w| w * w).sum();
+    Ok(usize_to_f64(len) * sum_sq / (sum * sum))
+}
+
+// ============================================================================
+// SHORT-TIME FOURIER TRANSFORM
+// ============================================================================
+
+/// Value scale of a magnitude spectrogram.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectrogramScale {
+    /// `|X|`
+    Linear = 0,
+    /// `20 * log10(|X|)`, floored at `SPECTROGRAM_FLOOR_DB`.
+    Decibels = 1,
+}
+
+/// dB value written for silent bins instead of `-inf`.
+const SPECTROGRAM_FLOOR_DB: f64 = -240.0;
+
+/// Frame count and bins per frame for a signal of `signal_len` samples.
+/// Frames start every `hop` samples and are not padded, so trailing samples
+/// that do not fill a whole frame are dropped.
+fn stft_layout(
+    signal_len: usize,
+    frame_size: usize,
+    hop: usize,
+    what: &str,
+) -> Result<(usize, usize), WasmError> {
+    if frame_size == 0 || hop == 0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: frame size and hop must be positive, got {frame_size} and {hop}"),
+        ));
+    }
+    let frames = if signal_len < frame_size {
+        0
+    } else {
+        1 + (signal_len - frame_size) / hop
+    };
+    let bins = half_spectrum_len(frame_size);
+    frames.checked_mul(bins).ok_or_else(|| {
+        WasmError::new(
+            ErrorCode::SizeOverflow,
+            format!("{what}: {frames} frames x {bins} bins overflows"),
+        )
+    })?;
+    Ok((frames, bins))
+}
+
+/// Windows each frame of `signal`, transforms it with `rfft_impl` and hands the
+/// half spectrum of frame `f` to `emit(f, re, im)`.
+fn stft_frames(
+    signal: &[f64],
+    frames: usize,
+    hop: usize,
+    window: &[f64],
+    mut emit: impl FnMut(usize, &[f64], &[f64]),
+) -> Result<(), WasmError> {
+    let frame_size = window.len();
+    let bins = half_spectrum_len(frame_size);
+    let mut frame = vec![0.0; frame_size];
+    let mut re = vec![0.0; bins];
+    let mut im = vec![0.0; bins];
+    for f in 0..frames {
+        let start = f * hop;
+        for ((value, &sample), &w) in frame
+            .iter_mut()
+            .zip(&signal[start..start + frame_size])
+            .zip(window)
+        {
+            *value = sample * w;
+        }
+        rfft_impl(&frame, &mut re, &mut im)?;
+        emit(f, &re, &im);
+    }
+    Ok(())
+}
+
+/// Number of STFT frames `stft` produces for a signal of `signal_len` samples.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `frame_size` or `hop` is zero, and `SizeOverflow` if the frame
+/// count times the bin count overflows.
+#[wasm_bindgen]
+pub fn stft_frame_count(
+    signal_len: usize,
+    frame_size: usize,
+    hop: usize,
+) -> Result<usize, JsError> {
+    Ok(stft_layout(signal_len, frame_size, hop, "stft_frame_count")?.0)
+}
+
+/// Complex STFT. Each frame of `frame_size` samples, starting every `hop`
+/// samples, is multiplied by a periodic window and transformed with `rfft`.
+/// Frame `f`, bin `k` lands at `out[f * (frame_size / 2 + 1) + k]`.
+/// Returns the frame count (see `stft_frame_count`).
+///
+/// # Errors
+///
+/// As `stft_frame_count`, plus `BufferTooSmall` if `out_re` or `out_im` is shorter than
+/// frames times `frame_size / 2 + 1`, and `InvalidArgument` for a bad window parameter
+/// (see `generate_window`).
+#[wasm_bindgen]
+pub fn stft(
+    signal: &[f64],
+    out_re: &mut [f64],
+    out_im: &mut [f64],
+    frame_size: usize,
+    hop: usize,
+    window: WindowKind,
+    window_param: f64,
+) -> Result<usize, JsError> {
+    let (frames, bins) = stft_layout(signal.len(), frame_size, hop, "stft")?;
+    require_len(out_re.len(), frames * bins, "stft: out_re")?;
+    require_len(out_im.len(), frames * bins, "stft: out_im")?;
+    let window = window_vec(frame_size, window, window_param, true, "stft")?;
+    stft_frames(signal, frames, hop, &window, |f, re, im| {
+        out_re[f * bins..(f + 1) * bins].copy_from_slice(re);
+        out_im[f * bins..(f + 1) * bins].copy_from_slice(im);
+    })?;
+    Ok(frames)
+}
+
+/// Magnitude spectrogram with the same frame layout as `stft`, in linear or
+/// dB scale. Returns the frame count.
+///
+/// # Errors
+///
+/// As `stft`, with `out` checked in place of `out_re` and `out_im`.
+#[wasm_bindgen]
+pub fn stft_magnitude(
+    signal: &[f64],
+    out: &mut [f64],
+    frame_size: usize,
+    hop: usize,
+    window: WindowKind,
+    window_param: f64,
+    scale: SpectrogramScale,
+) -> Result<usize, JsError> {
+    let (frames, bins) = stft_layout(signal.len(), frame_size, hop, "stft_magnitude")?;
+    require_len(out.len(), frames * bins, "stft_magnitude: out")?;
+    let window = window_vec(frame_size, window, window_param, true, "stft_magnitude")?;
+    stft_frames(signal, frames, hop, &window, |f, re, im| {
+        let row = &mut out[f * bins..(f + 1) * bins];
+        for ((value, &r), &i) in row.iter_mut().zip(re).zip(im) {
+            let magnitude = r.hypot(i);
+            *value = match scale {
+                SpectrogramScale::Linear => magnitude,
+                SpectrogramScale::Decibels => (20.0 * magnitude.log10()).max(SPECTROGRAM_FLOOR_DB),
+            };
+        }
+    })?;
+    Ok(frames)
+}
+
+/// Inverse of `stft` by weighted overlap-add: each frame is inverse transformed,
+/// multiplied by the window again and summed, then divided by the summed
+/// squared window. `output.len()` fixes the frame count; samples no frame
+/// covers, or where the window sum vanishes, are set to 0.
+///
+/// # Errors
+///
+/// As `stft`, with `re` and `im` checked against the frame count `output.len()`
+/// implies.
+#[wasm_bindgen]
+pub fn istft(
+    re: &[f64],
+    im: &[f64],
+    output: &mut [f64],
+    frame_size: usize,
+    hop: usize,
+    window: WindowKind,
+    window_param: f64,
+) -> Result<(), JsError> {
+    let (frames, bins) = stft_layout(output.len(), frame_size, hop, "istft")?;
+    require_len(re.len(), frames * bins, "istft: re")?;
+    require_len(im.len(), frames * bins, "istft: im")?;
+    let window = window_vec(frame_size, window, window_param, true, "istft")?;
+
+    output.fill(0.0);
+    let mut weight = vec![0.0; output.len()];
+    let mut frame = vec![0.0; frame_size];
+    for f in 0..frames {
+        let spectrum = f * bins..(f + 1) * bins;
+        irfft_impl(&re[spectrum.clone()], &im[spectrum], &mut frame)?;
+        let start = f * hop;
+        for (i, (&sample, &w)) in frame.iter().zip(&window).enumerate() {
+            output[start + i] += sample * w;
+            weight[start + i] += w * w;
+        }
+    }
+    for (sample, &w) in output.iter_mut().zip(&weight) {
+        *sample = if w > 1e-10 { *sample / w } else { 0.0 };
+    }
+    Ok(())
+}
+
+// ============================================================================
+// MEL FEATURES
+// ============================================================================
+
+/// Mel band powers are clamped to this before taking logs (-100 dB).
+const MEL_POWER_FLOOR: f64 = 1e-10;
+
+/// HTK mel scale.
+fn hz_to_mel(frequency: f64) -> f64 {
+    2595.0 * (1.0 + frequency / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f64) -> f64 {
+    700.0 * (10f64.powf(mel / 2595.0) - 1.0)
+}
+
+/// Row-major `[n_mels][frame_size / 2 + 1]` triangular filters with unit peaks,
+/// centred on `n_mels` points evenly spaced in mel between `f_min` and `f_max`.
+fn mel_filterbank_impl(
+    n_mels: usize,
+    frame_size: usize,
+    sample_rate: f64,
+    f_min: f64,
+    f_max: f64,
+    what: &str,
+) -> Result<Vec<f64>, WasmError> {
+    if n_mels == 0 || frame_size < 2 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: need n_mels >= 1 and frame_size >= 2, got {n_mels} and {frame_size}"),
+        ));
+    }
+    let nyquist = sample_rate / 2.0;
+    // NaN fails every comparison, and f_max <= nyquist keeps both bounds finite.
+    let valid = sample_rate.is_finite() && 0.0 <= f_min && f_min < f_max && f_max <= nyquist;
+    if !valid {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: need 0 <= f_min < f_max <= {nyquist} Hz, got {f_min}..{f_max}"),
+        ));
+    }
+
+    let bins = half_spectrum_len(frame_size);
+    let (mel_min, mel_max) = (hz_to_mel(f_min), hz_to_mel(f_max));
+    let edges: Vec<f64> = (0..n_mels + 2)
+        .map(|i| {
+            mel_to_hz(mel_min + (mel_max - mel_min) * usize_to_f64(i) / usize_to_f64(n_mels + 1))
+        })
+        .collect();
+    let mut bank = vec![0.0; n_mels * bins];
+    for (m, row) in bank.chunks_exact_mut(bins).enumerate() {
+        let (left, center, right) = (edges[m], edges[m + 1], edges[m + 2]);
+        for (k, weight) in row.iter_mut().enumerate() {
+            let frequency = usize_to_f64(k) * sample_rate / usize_to_f64(frame_size);
+            let rising = (frequency - left) / (center - left);
+            let falling = (right - frequency) / (right - center);
+            *weight = rising.min(falling).max(0.0);
+        }
+    }
+    Ok(bank)
+}
+
+/// Writes the mel filterbank used by `MelExtractor` into `out`, row-major
+/// `[n_mels][frame_size / 2 + 1]`: triangles on the HTK mel scale
+/// (`2595 log10(1 + f / 700)`) with unit peaks, spanning `f_min..f_max` Hz.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `n_mels` is zero, `frame_size` is below 2, or the band does not
+/// satisfy `0 <= f_min < f_max <= sample_rate / 2`. `BufferTooSmall` if `out` has fewer
+/// than `n_mels * (frame_size / 2 + 1)` elements.
+#[wasm_bindgen]
+pub fn mel_filterbank(
+    out: &mut [f64],
+    n_mels: usize,
+    frame_size: usize,
+    sample_rate: f64,
+    f_min: f64,
+    f_max: f64,
+) -> Result<(), JsError> {
+    let bank = mel_filterbank_impl(
+        n_mels,
+        frame_size,
+        sample_rate,
+        f_min,
+        f_max,
+        "mel_filterbank",
+    )?;
+    require_len(out.len(), bank.len(), "mel_filterbank: out")?;
+    out[..bank.len()].copy_from_slice(&bank);
+    Ok(())
+}
+
+/// Row-major `[count][len]` orthonormal DCT-II basis.
+fn dct2_matrix(count: usize, len: usize) -> Vec<f64> {
+    use std::f64::consts::PI;
+    let n = usize_to_f64(len);
+    let mut matrix = vec![0.0; count * len];
+    for (k, row) in matrix.chunks_exact_mut(len).enumerate() {
+        let scale = if k == 0 {
+            (1.0 / n).sqrt()
+        } else {
+            (2.0 / n).sqrt()
+        };
+        for (i, value) in row.iter_mut().enumerate() {
+            *value = scale * (PI / n * (usize_to_f64(i) + 0.5) * usize_to_f64(k)).cos();
+        }
+    }
+    matrix
+}
+
+/// Orthonormal DCT-II (`norm = "ortho"`): the first `output.len()`
+/// coefficients of `input`, at most `input.len()`.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `input` is shorter than `output`.
+#[wasm_bindgen]
+pub fn dct2(input: &[f64], output: &mut [f64]) -> Result<(), JsError> {
+    require_len(input.len(), output.len(), "dct2: input")?;
+    let matrix = dct2_matrix(output.len(), input.len());
+    if !input.is_empty() {
+        for (value, row) in output.iter_mut().zip(matrix.chunks_exact(input.len())) {
+            *value = dot_product_f64(row, input);
+        }
+    }
+    Ok(())
+}
+
+/// Mel-spectrogram and MFCC extractor for speech features. Frames of
+/// `frame_size` samples every `hop` samples (no padding, as in `stft`) get a
+/// periodic Hann window; their power spectra are summed through
+/// `mel_filterbank`. All outputs are frame-major `[frame][value]`.
+#[wasm_bindgen]
+pub struct MelExtractor {
+    n_mels: usize,
+    hop: usize,
+    window: Vec<f64>,
+    filterbank: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl MelExtractor {
+    /// Mel analysis of `frame_size`-sample Hann frames every `hop` samples, with
+    /// `n_mels` bands spanning `f_min..f_max` Hz.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `hop` is zero or the filterbank arguments are invalid (see
+    /// `mel_filterbank`).
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        sample_rate: f64,
+        frame_size: usize,
+        hop: usize,
+        n_mels: usize,
+        f_min: f64,
+        f_max: f64,
+    ) -> Result<MelExtractor, JsError> {
+        stft_layout(0, frame_size, hop, "MelExtractor")?;
+        let filterbank = mel_filterbank_impl(
+            n_mels,
+            frame_size,
+            sample_rate,
+            f_min,
+            f_max,
+            "MelExtractor",
+        )?;
+        let window = window_vec(frame_size, WindowKind::Hann, 0.0, true, "MelExtractor")?;
+        Ok(MelExtractor {
+            n_mels,
+            hop,
+            window,
+            filterbank,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn frame_size(&self) -> usize {
+        self.window.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn n_mels(&self) -> usize {
+        self.n_mels
+    }
+
+    /// Frames produced for a signal of `signal_len` samples.
+    ///
+    /// # Errors
+    ///
+    /// `SizeOverflow` if the frame count times `n_mels` overflows.
+    pub fn frame_count(&self, signal_len: usize) -> Result<usize, JsError> {
+        Ok(self.layout(signal_len, "MelExtractor.frame_count")?)
+    }
+
+    /// Mel band powers, `out[frame * n_mels + m]`. Returns the frame count.
+    ///
+    /// # Errors
+    ///
+    /// As `frame_count`, plus `BufferTooSmall` if `out` is shorter than the frame count
+    /// times `n_mels`.
+    pub fn mel_spectrogram(&self, signal: &[f64], out: &mut [f64]) -> Result<usize, JsError> {
+        let frames = self.layout(signal.len(), "MelExtractor.mel_spectrogram")?;
+        require_len(
+            out.len(),
+            frames * self.n_mels,
+            "MelExtractor.mel_spectrogram: out",
+        )?;
+        self.mel_frames(signal, frames, |f, mel| {
+            out[f * self.n_mels..(f + 1) * self.n_mels].copy_from_slice(mel);
+        })?;
+        Ok(frames)
+    }
+
+    /// Mel band powers in dB (`10 log10`, floored at -100 dB). Returns the
+    /// frame count.
+    ///
+    /// # Errors
+    ///
+    /// As `mel_spectrogram`.
+    pub fn log_mel_spectrogram(&self, signal: &[f64], out: &mut [f64]) -> Result<usize, JsError> {
+        let frames = self.layout(signal.len(), "MelExtractor.log_mel_spectrogram")?;
+        require_len(
+            out.len(),
+            frames * self.n_mels,
+            "MelExtractor.log_mel_spectrogram: out",
+        )?;
+        self.mel_frames(signal, frames, |f, mel| {
+            for (value, &power) in out[f * self.n_mels..].iter_mut().zip(mel) {
+                *value = mel_decibels(power);
+            }
+        })?;
+        Ok(frames)
+    }
+
+    /// First `n_coeffs` (`1..=n_mels`) MFCCs per frame: the orthonormal DCT-II
+    /// of the log-mel spectrum, `out[frame * n_coeffs + c]`. Returns the frame
+    /// count.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `n_coeffs` is outside `1..=n_mels`, `SizeOverflow` as in
+    /// `frame_count`, and `BufferTooSmall` if `out` is shorter than the frame count
+    /// times `n_coeffs`.
+    pub fn mfcc(&self, signal: &[f64], out: &mut [f64], n_coeffs: usize) -> Result<usize, JsError> {
+        if n_coeffs == 0 || n_coeffs > self.n_mels {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "MelExtractor.mfcc: n_coeffs must be in 1..={}, got {n_coeffs}",
+                    self.n_mels
+                ),
+            )
+            .into());
+        }
+        let frames = self.layout(signal.len(), "MelExtractor.mfcc")?;
+        require_len(out.len(), frames * n_coeffs, "MelExtractor.mfcc: out")?;
+        let basis = dct2_matrix(n_coeffs, self.n_mels);
+        let mut log_mel = vec![0.0; self.n_mels];
+        self.mel_frames(signal, frames, |f, mel| {
+            for (value, &power) in log_mel.iter_mut().zip(mel) {
+                *value = mel_decibels(power);
+            }
+            let coefficients = &mut out[f * n_coeffs..(f + 1) * n_coeffs];
+            for (value, row) in coefficients.iter_mut().zip(basis.chunks_exact(self.n_mels)) {
+                *value = dot_product_f64(row, &log_mel);
+            }
+        })?;
+        Ok(frames)
+    }
+}
+
+fn mel_decibels(power: f64) -> f64 {
+    10.0 * power.max(MEL_POWER_FLOOR).log10()
+}
+
+impl MelExtractor {
+    fn layout(&self, signal_len: usize, what: &str) -> Result<usize, WasmError> {
+        let (frames, _) = stft_layout(signal_len, self.window.len(), self.hop, what)?;
+        frames.checked_mul(self.n_mels).ok_or_else(|| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                format!(
+                    "{what}: {frames} frames x {} mel bands overflows",
+                    self.n_mels
+                ),
+            )
+        })?;
+        Ok(frames)
+    }
+
+    /// Hands the mel band powers of each frame to `emit(frame, mel)`.
+    fn mel_frames(
+        &self,
+        signal: &[f64],
+        frames: usize,
+        mut emit: impl FnMut(usize, &[f64]),
+    ) -> Result<(), WasmError> {
+        let bins = half_spectrum_len(self.window.len());
+        let mut power = vec![0.0; bins];
+        let mut mel = vec![0.0; self.n_mels];
+        stft_frames(signal, frames, self.hop, &self.window, |f, re, im| {
+            for ((p, &r), &i) in power.iter_mut().zip(re).zip(im) {
+                *p = r * r + i * i;
+            }
+            for (value, row) in mel.iter_mut().zip(self.filterbank.chunks_exact(bins)) {
+                *value = dot_product_f64(row, &power);
+            }
+            emit(f, &mel);
+        })
+    }
+}
+
+// ============================================================================
+// SPECTRAL DENSITY & SIGNAL METRICS
+// ============================================================================
+
+/// Bins on each side of a tone's peak counted as that tone; covers the
+/// Blackman-Harris main lobe (+-4 bins) with one bin to spare.
+const TONE_HALF_WIDTH_BINS: usize = 5;
+
+/// One-sided power spectral density by Welch's method: segments of
+/// `segment_size` samples overlapping by `overlap` samples are windowed
+/// (periodic) and their periodograms averaged. `out[k]` holds the density at
+/// `k * sample_rate / segment_size` Hz in units^2/Hz, so summing `out` times the
+/// bin width gives the mean power. No detrending is applied. Returns the
+/// number of segments averaged.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not finite and positive, `overlap` is not
+/// below `segment_size`, or `window_param` is invalid. `BufferTooSmall` if `signal` is
+/// shorter than one segment or `out` has fewer than `segment_size / 2 + 1` elements.
+#[wasm_bindgen]
+pub fn welch_psd(
+    signal: &[f64],
+    out: &mut [f64],
+    sample_rate: f64,
+    segment_size: usize,
+    overlap: usize,
+    window: WindowKind,
+    window_param: f64,
+) -> Result<usize, JsError> {
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("welch_psd: sample rate must be positive, got {sample_rate}"),
+        )
+        .into());
+    }
+    if overlap >= segment_size {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("welch_psd: overlap {overlap} must be below the segment size {segment_size}"),
+        )
+        .into());
+    }
+    let hop = segment_size - overlap;
+    let (segments, bins) = stft_layout(signal.len(), segment_size, hop, "welch_psd")?;
+    require_len(signal.len(), segment_size, "welch_psd: signal")?;
+    require_len(out.len(), bins, "welch_psd: out")?;
+
+    let window = window_vec(segment_size, window, window_param, true, "welch_psd")?;
+    let power: f64 = window.iter().map(|w| w * w).sum();
+    let out = &mut out[..bins];
+    out.fill(0.0);
+    stft_frames(signal, segments, hop, &window, |_, re, im| {
+        for ((value, &r), &i) in out.iter_mut().zip(re).zip(im) {
+            *value += r * r + i * i;
+        }
+    })?;
+    let scale = 1.0 / (sample_rate * power * usize_to_f64(segments));
+    for (k, value) in out.iter_mut().enumerate() {
+        // Fold negative frequencies in; DC and an even-length Nyquist bin have no twin.
+        let one_sided = if k == 0 || 2 * k == segment_size {
+            1.0
+        } else {
+            2.0
+        };
+        *value *= scale * one_sided;
+    }
+    Ok(segments)
+}
+
+/// Root mean square of `signal` (0 when empty).
+#[wasm_bindgen]
+#[must_use]
+pub fn rms(signal: &[f64]) -> f64 {
+    if signal.is_empty() {
+        return 0.0;
+    }
+    (signal.iter().map(|x| x * x).sum::<f64>() / usize_to_f64(signal.len())).sqrt()
+}
+
+/// Largest absolute sample of `signal` (0 when empty).
+#[wasm_bindgen]
+#[must_use]
+pub fn peak_amplitude(signal: &[f64]) -> f64 {
+    signal.iter().fold(0.0, |peak, x| x.abs().max(peak))
+}
+
+/// Peak over RMS (sqrt(2) for a sine); 0 for silence.
+#[wasm_bindgen]
+#[must_use]
+pub fn crest_factor(signal: &[f64]) -> f64 {
+    let level = rms(signal);
+    if level > 0.0 {
+        peak_amplitude(signal) / level
+    } else {
+        0.0
+    }
+}
+
+/// Distortion and noise figures of a signal around its fundamental, from
+/// `analyze_harmonics`. All ratios are in dB.
+#[wasm_bindgen]
+pub struct HarmonicAnalysis {
+    fundamental: f64,
+    fundamental_power: f64,
+    harmonic_power: f64,
+    noise_power: f64,
+}
+
+#[wasm_bindgen]
+impl HarmonicAnalysis {
+    /// Frequency of the fundamental's peak bin, in Hz.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn fundamental(&self) -> f64 {
+        self.fundamental
+    }
+
+    /// Fundamental over everything except DC and harmonics.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn snr_db(&self) -> f64 {
+        power_ratio_db(self.fundamental_power, self.noise_power)
+    }
+
+    /// Harmonics over the fundamental (negative for a clean tone).
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn thd_db(&self) -> f64 {
+        power_ratio_db(self.harmonic_power, self.fundamental_power)
+    }
+
+    /// Fundamental over noise plus harmonics.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn sinad_db(&self) -> f64 {
+        power_ratio_db(
+            self.fundamental_power,
+            self.noise_power + self.harmonic_power,
+        )
+    }
+}
+
+fn power_ratio_db(numerator: f64, denominator: f64) -> f64 {
+    10.0 * (numerator / denominator).log10()
+}
+
+/// Splits the Blackman-Harris periodogram of `signal` into fundamental,
+/// harmonic (orders 2..=`harmonics`, folded back below Nyquist) and noise
+/// power. `fundamental` is the expected tone in Hz; 0 picks the strongest
+/// non-DC bin. Tones are summed over +-5 bins.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `signal` has fewer than 20 samples. `InvalidArgument` unless
+/// `sample_rate` is finite and positive with `0 <= fundamental < sample_rate / 2`, if
+/// `harmonics` is below 2 (no THD to measure), or if no bin lies above the DC region.
+#[wasm_bindgen]
+pub fn analyze_harmonics(
+    signal: &[f64],
+    sample_rate: f64,
+    fundamental: f64,
+    harmonics: usize,
+) -> Result<HarmonicAnalysis, JsError> {
+    let n = signal.len();
+    require_len(n, 4 * TONE_HALF_WIDTH_BINS, "analyze_harmonics: signal")?;
+    let valid = sample_rate.is_finite() && 0.0 <= fundamental && fundamental < sample_rate / 2.0;
+    if !valid {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "analyze_harmonics: need sample_rate > 0 and 0 <= fundamental < Nyquist, \
+                 got {fundamental} Hz at {sample_rate} Hz"
+            ),
+        )
+        .into());
+    }
+    if harmonics < 2 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("analyze_harmonics: need at least 2 harmonic orders, got {harmonics}"),
+        )
+        .into());
+    }
+
+    let window = window_vec(
+        n,
+        WindowKind::BlackmanHarris,
+        0.0,
+        true,
+        "analyze_harmonics",
+    )?;
+    let windowed: Vec<f64> = signal.iter().zip(&window).map(|(x, w)| x * w).collect();
+    let bins = half_spectrum_len(n);
+    let mut re = vec![0.0; bins];
+    let mut im = vec![0.0; bins];
+    rfft_impl(&windowed, &mut re, &mut im)?;
+    let power: Vec<f64> = re.iter().zip(&im).map(|(r, i)| r * r + i * i).collect();
+
+    let bin_width = sample_rate / usize_to_f64(n);
+    let peak_near = |center: usize| {
+        let lo = center.saturating_sub(2).max(TONE_HALF_WIDTH_BINS + 1);
+        let hi = (center + 2).min(bins - 1);
+        (lo..=hi).max_by(|&a, &b| power[a].total_cmp(&power[b]))
+    };
+    let peak = if fundamental > 0.0 {
+        peak_near(f64_to_usize((fundamental / bin_width).round()))
+    } else {
+        (TONE_HALF_WIDTH_BINS + 1..bins).max_by(|&a, &b| power[a].total_cmp(&power[b]))
+    };
+    let Some(peak) = peak else {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            "analyze_harmonics: no bins above DC",
+        )
+        .into());
+    };
+
+    // Claim each bin once: DC first, then the fundamental, then harmonics.
+    let mut claimed = vec![false; bins];
+    let mut take = |center: usize| -> f64 {
+        let lo = center.saturating_sub(TONE_HALF_WIDTH_BINS);
+        let hi = (center + TONE_HALF_WIDTH_BINS).min(bins - 1);
+        let mut sum = 0.0;
+        for k in lo..=hi {
+            if !claimed[k] {
+                claimed[k] = true;
+                sum += power[k];
+            }
+        }
+        sum
+    };
+    take(0);
+    let fundamental_power = take(peak);
+    let peak_hz = usize_to_f64(peak) * bin_width;
+    let mut harmonic_power = 0.0;
+    for order in 2..=harmonics {
+        let aliased = (usize_to_f64(order) * peak_hz).rem_euclid(sample_rate);
+        let folded = aliased.min(sample_rate - aliased);
+        if let Some(bin) = peak_near(f64_to_usize((folded / bin_width).round())) {
+            harmonic_power += take(bin);
+        }
+    }
+    let noise_power = power
+        .iter()
+        .zip(&claimed)
+        .filter(|&(_, &claimed)| !claimed)
+        .map(|(p, _)| p)
+        .sum();
+
+    Ok(HarmonicAnalysis {
+        fundamental: peak_hz,
+        fundamental_power,
+        harmonic_power,
+        noise_power,
+    })
+}
+
+// ============================================================================
+// ANALYTIC SIGNAL
+// ============================================================================
+
+/// Analytic signal `x + i H{x}` of `signal` (FFT method: negative frequencies
+/// removed, positive ones doubled). The real part reproduces `signal`.
+fn analytic_impl(signal: &[f64]) -> Result<(Vec<f64>, Vec<f64>), WasmError> {
+    let n = signal.len();
+    let mut re = signal.to_vec();
+    let mut im = vec![0.0; n];
+    if n == 0 {
+        return Ok((re, im));
+    }
+    let kernel = kernel_for(n)?;
+    kernel.forward(&mut re, &mut im);
+    // DC and (for even n) Nyquist stay as they are.
+    let positive_end = n.div_ceil(2);
+    for k in 1..positive_end {
+        re[k] *= 2.0;
+        im[k] *= 2.0;
+    }
+    let negative_start = n / 2 + 1;
+    re[negative_start..].fill(0.0);
+    im[negative_start..].fill(0.0);
+    kernel.inverse(&mut re, &mut im);
+    Ok((re, im))
+}
+
+/// Writes the analytic signal of `signal`: `out_re` gets the signal itself,
+/// `out_im` its Hilbert transform. Any length is accepted; the transform is
+/// circular, so expect edge effects unless the buffer holds whole periods.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `out_re` or `out_im` is shorter than `signal`, and
+/// `SizeOverflow` if no FFT plan fits for `signal.len()`.
+#[wasm_bindgen]
+pub fn analytic_signal(
+    signal: &[f64],
+    out_re: &mut [f64],
+    out_im: &mut [f64],
+) -> Result<(), JsError> {
+    require_len(out_re.len(), signal.len(), "analytic_signal: out_re")?;
+    require_len(out_im.len(), signal.len(), "analytic_signal: out_im")?;
+    let (re, im) = analytic_impl(signal)?;
+    out_re[..re.len()].copy_from_slice(&re);
+    out_im[..im.len()].copy_from_slice(&im);
+    Ok(())
+}
+
+/// Instantaneous amplitude (`|x + i H{x}|`) into `envelope` and phase into
+/// `phase`, in radians; wrapped to `[-pi, pi]` unless `unwrap_phase` is set.
+/// The envelope demodulates AM and traces onsets.
+///
+/// # Errors
+///
+/// As `analytic_signal`, with `envelope` and `phase` as the outputs.
+#[wasm_bindgen]
+pub fn hilbert_envelope(
+    signal: &[f64],
+    envelope: &mut [f64],
+    phase: &mut [f64],
+    unwrap_phase: bool,
+) -> Result<(), JsError> {
+    require_len(envelope.len(), signal.len(), "hilbert_envelope: envelope")?;
+    require_len(phase.len(), signal.len(), "hilbert_envelope: phase")?;
+    let (re, im) = analytic_impl(signal)?;
+    let mut previous = 0.0;
+    for (i, (&r, &q)) in re.iter().zip(&im).enumerate() {
+        envelope[i] = r.hypot(q);
+        let mut angle = q.atan2(r);
+        if unwrap_phase && i > 0 {
+            let turns = ((angle - previous) / std::f64::consts::TAU).round();
+            angle -= turns * std::f64::consts::TAU;
+        }
+        phase[i] = angle;
+        previous = angle;
+    }
+    Ok(())
+}
+
+/// Instantaneous frequency in Hz: the phase advance of the analytic signal
+/// per sample, averaged over both neighbours (one-sided at the ends). Valid up
+/// to Nyquist without unwrapping.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not finite, and otherwise as
+/// `analytic_signal`, with `out` as the output.
+#[wasm_bindgen]
+pub fn instantaneous_frequency(
+    signal: &[f64],
+    sample_rate: f64,
+    out: &mut [f64],
+) -> Result<(), JsError> {
+    require_finite(sample_rate, "instantaneous_frequency: sample_rate")?;
+    require_len(out.len(), signal.len(), "instantaneous_frequency: out")?;
+    let n = signal.len();
+    if n < 2 {
+        out[..n].fill(0.0);
+        return Ok(());
+    }
+    let (re, im) = analytic_impl(signal)?;
+    // arg(a[i + 1] * conj(a[i])): the wrapped phase step between samples.
+    let steps: Vec<f64> = (0..n - 1)
+        .map(|i| {
+            let cross_re = re[i + 1] * re[i] + im[i + 1] * im[i];
+            let cross_im = im[i + 1] * re[i] - re[i + 1] * im[i];
+            cross_im.atan2(cross_re)
+        })
+        .collect();
+    let scale = sample_rate / std::f64::consts::TAU;
+    out[0] = steps[0] * scale;
+    out[n - 1] = steps[n - 2] * scale;
+    for i in 1..n - 1 {
+        out[i] = f64::midpoint(steps[i - 1], steps[i]) * scale;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// FIR FILTERS
+// ============================================================================
+
+/// Frequency band selected by a filter design.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterBand {
+    /// Passes below `f1`.
+    Lowpass = 0,
+    /// Passes above `f1`.
+    Highpass = 1,
+    /// Passes between `f1` and `f2`.
+    Bandpass = 2,
+    /// Rejects between `f1` and `f2`.
+    Bandstop = 3,
+}
+
+/// Grid points per extremal frequency in the equiripple design.
+const REMEZ_GRID_DENSITY: usize = 16;
+const REMEZ_MAX_ITERATIONS: usize = 100;
+
+/// Checks the cutoffs of a `band` design and returns them normalized to
+/// cycles per sample. `f2` is ignored for lowpass and highpass.
+fn normalized_cutoffs(
+    band: FilterBand,
+    sample_rate: f64,
+    f1: f64,
+    f2: f64,
+    what: &str,
+) -> Result<(f64, f64), WasmError> {
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: sample rate must be positive, got {sample_rate}"),
+        ));
+    }
+    let nyquist = sample_rate / 2.0;
+    let in_range = |f: f64| f.is_finite() && f > 0.0 && f < nyquist;
+    let two_edges = matches!(band, FilterBand::Bandpass | FilterBand::Bandstop);
+    if !in_range(f1) || (two_edges && !(in_range(f2) && f1 < f2)) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: cutoffs {f1} / {f2} Hz must satisfy 0 < f1 < f2 < {nyquist}"),
+        ));
+    }
+    Ok((f1 / sample_rate, f2 / sample_rate))
+}
+
+/// Highpass and bandstop filters need a nonzero response at Nyquist, which only
+/// odd-length (type I) linear-phase filters have.
+fn require_odd_taps(band: FilterBand, taps: usize, what: &str) -> Result<(), WasmError> {
+    if matches!(band, FilterBand::Highpass | FilterBand::Bandstop) && taps.is_multiple_of(2) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!("{what}: {band:?} needs an odd number of taps, got {taps}"),
+        ));
+    }
+    Ok(())
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Magnitude of the frequency response of `taps` at `f` cycles per sample.
+fn fir_response_at(taps: &[f64], f: f64) -> f64 {
+    let (re, im) = taps
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (n, &h)| {
+            let angle = -2.0 * std::f64::consts::PI * f * usize_to_f64(n);
+            (re + h * angle.cos(), im + h * angle.sin())
+        });
+    re.hypot(im)
+}
+
+/// Windowed-sinc FIR design. The length of `taps` sets the filter order;
+/// highpass and bandstop need an odd length. Frequencies are in Hz, and the
+/// result is scaled to unit gain at DC (lowpass, bandstop), Nyquist (highpass)
+/// or the band center (bandpass). The window is symmetric; `window_param` is
+/// as in `generate_window`.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not positive, the cutoffs do not satisfy
+/// `0 < f1 < f2 < sample_rate / 2` (`f2` only for two-edge bands), or `window_param` is
+/// invalid. `InvalidDimensions` if `taps` is empty, or has even length for highpass or
+/// bandstop.
+#[wasm_bindgen]
+pub fn fir_design_windowed(
+    taps: &mut [f64],
+    band: FilterBand,
+    sample_rate: f64,
+    f1: f64,
+    f2: f64,
+    window: WindowKind,
+    window_param: f64,
+) -> Result<(), JsError> {
+    let what = "fir_design_windowed";
+    let (lo, hi) = normalized_cutoffs(band, sample_rate, f1, f2, what)?;
+    require_odd_taps(band, taps.len(), what)?;
+    let window = window_vec(taps.len(), window, window_param, false, what)?;
+
+    let center = usize_to_f64(taps.len() - 1) / 2.0;
+    let lowpass = |fc: f64, t: f64| 2.0 * fc * sinc(2.0 * fc * t);
+    for (n, (tap, &w)) in taps.iter_mut().zip(&window).enumerate() {
+        let t = usize_to_f64(n) - center;
+        let impulse = if t == 0.0 { 1.0 } else { 0.0 };
+        let ideal = match band {
+            FilterBand::Lowpass => lowpass(lo, t),
+            FilterBand::Highpass => impulse - lowpass(lo, t),
+            FilterBand::Bandpass => lowpass(hi, t) - lowpass(lo, t),
+            FilterBand::Bandstop => impulse - (lowpass(hi, t) - lowpass(lo, t)),
+        };
+        *tap = ideal * w;
+    }
+
+    let reference = match band {
+        FilterBand::Lowpass | FilterBand::Bandstop => 0.0,
+        FilterBand::Highpass => 0.5,
+        FilterBand::Bandpass => f64::midpoint(lo, hi),
+    };
+    let gain = fir_response_at(taps, reference);
+    if gain > 0.0 {
+        for tap in taps.iter_mut() {
+            *tap /= gain;
+        }
+    }
+    Ok(())
+}
+
+/// Equiripple (minimax) FIR design with the Parks-McClellan / Remez exchange
+/// algorithm. `taps.len()` must be odd. Each cutoff is the center of a
+/// transition band `transition_width` Hz wide; `stopband_weight` > 1 trades
+/// passband ripple for stopband attenuation.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if `taps.len()` is even or below 3. `InvalidArgument` for a bad
+/// sample rate or cutoffs (as in `fir_design_windowed`), a non-positive
+/// `stopband_weight` or `transition_width`, or a transition width that leaves a band
+/// empty.
+#[wasm_bindgen]
+pub fn fir_design_equiripple(
+    taps: &mut [f64],
+    band: FilterBand,
+    sample_rate: f64,
+    f1: f64,
+    f2: f64,
+    transition_width: f64,
+    stopband_weight: f64,
+) -> Result<(), JsError> {
+    let what = "fir_design_equiripple";
+    let (lo, hi) = normalized_cutoffs(band, sample_rate, f1, f2, what)?;
+    if taps.len() < 3 || taps.len().is_multiple_of(2) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            format!(
+                "{what}: needs an odd number of taps >= 3, got {}",
+                taps.len()
+            ),
+        )
+        .into());
+    }
+    if !(stopband_weight.is_finite() && stopband_weight > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: stopband weight must be positive, got {stopband_weight}"),
+        )
+        .into());
+    }
+    let half = transition_width / sample_rate / 2.0;
+    if !(half.is_finite() && half > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: transition width must be positive, got {transition_width}"),
+        )
+        .into());
+    }
+
+    // (start, end, desired gain, weight) in cycles per sample.
+    let pass = |a: f64, b: f64| (a, b, 1.0, 1.0);
+    let stop = |a: f64, b: f64| (a, b, 0.0, stopband_weight);
+    let bands = match band {
+        FilterBand::Lowpass => vec![pass(0.0, lo - half), stop(lo + half, 0.5)],
+        FilterBand::Highpass => vec![stop(0.0, lo - half), pass(lo + half, 0.5)],
+        FilterBand::Bandpass => vec![
+            stop(0.0, lo - half),
+            pass(lo + half, hi - half),
+            stop(hi + half, 0.5),
+        ],
+        FilterBand::Bandstop => vec![
+            pass(0.0, lo - half),
+            stop(lo + half, hi - half),
+            pass(hi + half, 0.5),
+        ],
+    };
+    if bands.iter().any(|&(start, end, _, _)| start >= end) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: transition width {transition_width} Hz leaves an empty band"),
+        )
+        .into());
+    }
+
+    remez_type1(taps, &bands);
+    Ok(())
+}
+
+/// Dense frequency grid of the Remez exchange: frequency, desired gain and weight.
+struct RemezGrid {
+    freq: Vec<f64>,
+    desired: Vec<f64>,
+    weight: Vec<f64>,
+    /// Index of the first grid point of each band.
+    band_start: Vec<usize>,
+}
+
+impl RemezGrid {
+    fn new(bands: &[(f64, f64, f64, f64)], extremals: usize) -> Self {
+        let step = 0.5 / usize_to_f64(REMEZ_GRID_DENSITY * extremals);
+        let mut grid = Self {
+            freq: Vec::new(),
+            desired: Vec::new(),
+            weight: Vec::new(),
+            band_start: Vec::new(),
+        };
+        for &(start, end, desired, weight) in bands {
+            grid.band_start.push(grid.freq.len());
+            let mut f = start;
+            loop {
+                grid.freq.push(f.min(end));
+                grid.desired.push(desired);
+                grid.weight.push(weight);
+                if f >= end {
+                    break;
+                }
+                f += step;
+            }
+        }
+        grid
+    }
+
+    fn is_band_edge(&self, i: usize) -> bool {
+        self.band_start.contains(&i)
+            || self.band_start.contains(&(i + 1))
+            || i + 1 == self.freq.len()
+    }
+}
+
+/// Barycentric Lagrange interpolation of the amplitude response through
+/// `(x[i], c[i])` with `x = cos(2 pi f)`.
+struct Interpolant {
+    x: Vec<f64>,
+    c: Vec<f64>,
+    bary: Vec<f64>,
+}
+
+/// Barycentric weights `1 / prod_{j != i} 2 (x_i - x_j)`; the factor 2 keeps the
+/// products near unit magnitude for long filters.
+fn barycentric_weights(x: &[f64]) -> Vec<f64> {
+    (0..x.len())
+        .map(|i| {
+            let product: f64 = x
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &xj)| 2.0 * (x[i] - xj))
+                .product();
+            1.0 / product
+        })
+        .collect()
+}
+
+impl Interpolant {
+    fn eval(&self, x: f64) -> f64 {
+        let mut numerator = 0.0;
+        let mut denominator = 0.0;
+        for ((&xi, &ci), &bi) in self.x.iter().zip(&self.c).zip(&self.bary) {
+            let diff = x - xi;
+            if diff.abs() < 1e-14 {
+                return ci;
+            }
+            numerator += bi * ci / diff;
+            denominator += bi / diff;
+        }
+        numerator / denominator
+    }
+}
+
+/// One Remez step: the equiripple deviation for the current extremal set and
+/// the amplitude response that alternates through it.
+fn remez_interpolant(grid: &RemezGrid, extremals: &[usize]) -> (f64, Interpolant) {
+    let x: Vec<f64> = extremals
+        .iter()
+        .map(|&i| (2.0 * std::f64::consts::PI * grid.freq[i]).cos())
+        .collect();
+    let bary = barycentric_weights(&x);
+    let mut numerator = 0.0;
+    let mut denominator = 0.0;
+    for (k, (&i, &b)) in extremals.iter().zip(&bary).enumerate() {
+        let sign = if k.is_multiple_of(2) { 1.0 } else { -1.0 };
+        numerator += b * grid.desired[i];
+        denominator += sign * b / grid.weight[i];
+    }
+    let delta = numerator / denominator;
+
+    // Degree-M polynomial through the first M + 1 extremals.
+    let last = extremals.len() - 1;
+    let c = extremals[..last]
+        .iter()
+        .enumerate()
+        .map(|(k, &i)| {
+            let sign = if k.is_multiple_of(2) { 1.0 } else { -1.0 };
+            grid.desired[i] - sign * delta / grid.weight[i]
+        })
+        .collect();
+    let x = x[..last].to_vec();
+    let bary = barycentric_weights(&x);
+    (delta, Interpolant { x, c, bary })
+}
+
+/// Local extrema of the weighted error, reduced to an alternating set of
+/// `count` points. Returns `None` if too few alternations remain.
+fn remez_extremals(grid: &RemezGrid, error: &[f64], count: usize) -> Option<Vec<usize>> {
+    let len = error.len();
+    let mut picked: Vec<usize> = Vec::new();
+    for i in 0..len {
+        let e = error[i];
+        let left = if i == 0 || grid.band_start.contains(&i) {
+            None
+        } else {
+            Some(error[i - 1])
+        };
+        let right = if i + 1 == len || grid.band_start.contains(&(i + 1)) {
+            None
+        } else {
+            Some(error[i + 1])
+        };
+        let is_peak = |n: Option<f64>| n.is_none_or(|n| if e > 0.0 { e >= n } else { e <= n });
+        if !(grid.is_band_edge(i) || (is_peak(left) && is_peak(right))) {
+            continue;
+        }
+        match picked.last_mut() {
+            Some(last) if (error[*last] > 0.0) == (e > 0.0) => {
+                if e.abs() > error[*last].abs() {
+                    *last = i;
+                }
+            }
+            _ => picked.push(i),
+        }
+    }
+    while picked.len() > count {
+        if error[picked[0]].abs() < error[picked[picked.len() - 1]].abs() {
+            picked.remove(0);
+        } else {
+            picked.pop();
+        }
+    }
+    (picked.len() == count).then_some(picked)
+}
+
+/// Type I (odd length, symmetric) equiripple design over `bands`.
+fn remez_type1(taps: &mut [f64], bands: &[(f64, f64, f64, f64)]) {
+    let len = taps.len();
+    let order = (len - 1) / 2;
+    let count = order + 2;
+    let grid = RemezGrid::new(bands, count);
+    let points = grid.freq.len();
+
+    let mut extremals: Vec<usize> = (0..count).map(|k| k * (points - 1) / (count - 1)).collect();
+    // Very long, very selective designs can hit the f64 noise floor and start
+    // to diverge, so the iterate with the smallest peak error is kept.
+    let mut best: Option<(f64, Interpolant)> = None;
+    for _ in 0..REMEZ_MAX_ITERATIONS {
+        let (delta, interpolant) = remez_interpolant(&grid, &extremals);
+        let error: Vec<f64> = (0..points)
+            .map(|i| {
+                let x = (2.0 * std::f64::consts::PI * grid.freq[i]).cos();
+                grid.weight[i] * (grid.desired[i] - interpolant.eval(x))
+            })
+            .collect();
+        let max_error = error.iter().map(|e| e.abs()).fold(0.0, f64::max);
+        let next_extremals = remez_extremals(&grid, &error, count);
+        if best
+            .as_ref()
+            .is_none_or(|(best_error, _)| max_error < *best_error)
+        {
+            best = Some((max_error, interpolant));
+        }
+        let Some(next_extremals) = next_extremals else {
+            break;
+        };
+        extremals = next_extremals;
+        if max_error - delta.abs() <= 1e-9 * max_error {
+            break;
+        }
+    }
+    let Some((_, interpolant)) = best else {
+        return;
+    };
+
+    // Sample the amplitude response at the len DFT frequencies and invert.
+    let len_f64 = usize_to_f64(len);
+    let amplitude: Vec<f64> = (0..=order)
+        .map(|k| interpolant.eval((2.0 * std::f64::consts::PI * usize_to_f64(k) / len_f64).cos()))
+        .collect();
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let offset = usize_to_f64(n) - usize_to_f64(order);
+        let sum: f64 = amplitude
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, &a)| {
+                2.0 * a * (2.0 * std::f64::consts::PI * usize_to_f64(k) * offset / len_f64).cos()
+            })
+            .sum();
+        *tap = (amplitude[0] + sum) / len_f64;
+    }
+}
+
+/// Magnitude response `|H(f)|` of `taps` at each frequency (Hz) in `frequencies`.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `sample_rate` is not finite and positive, and `BufferTooSmall`
+/// if `out` is shorter than `frequencies`.
+#[wasm_bindgen]
+pub fn fir_magnitude_response(
+    taps: &[f64],
+    sample_rate: f64,
+    frequencies: &[f64],
+    out: &mut [f64],
+) -> Result<(), JsError> {
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("fir_magnitude_response: sample rate must be positive, got {sample_rate}"),
+        )
+        .into());
+    }
+    require_len(out.len(), frequencies.len(), "fir_magnitude_response: out")?;
+    for (value, &f) in out.iter_mut().zip(frequencies) {
+        *value = fir_response_at(taps, f / sample_rate);
+    }
+    Ok(())
+}
+
+fn dot_product_f64(a: &[f64], b: &[f64]) -> f64 {
+    let chunks_a = a.chunks_exact(4);
+    let chunks_b = b.chunks_exact(4);
+    let remainder_a = chunks_a.remainder();
+    let remainder_b = chunks_b.remainder();
+
+    let mut sum = [0.0f64; 4];
+    for (ca, cb) in chunks_a.zip(chunks_b) {
+        for lane in 0..4 {
+            sum[lane] += ca[lane] * cb[lane];
+        }
+    }
+    let mut total = sum[0] + sum[1] + sum[2] + sum[3];
+    for (&va, &vb) in remainder_a.iter().zip(remainder_b) {
+        total += va * vb;
+    }
+    total
+}
+
+/// Direct-form FIR filter of a whole buffer: `output[n] = sum_k taps[k] * input[n - k]`,
+/// with samples before the start taken as zero.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `output` is shorter than `input`.
+#[wasm_bindgen]
+pub fn fir_filter(input: &[f64], taps: &[f64], output: &mut [f64]) -> Result<(), JsError> {
+    require_len(output.len(), input.len(), "fir_filter: output")?;
+    if taps.is_empty() {
+        output[..input.len()].fill(0.0);
+        return Ok(());
+    }
+    let reversed: Vec<f64> = taps.iter().rev().copied().collect();
+    let mut padded = vec![0.0; taps.len() - 1];
+    padded.extend_from_slice(input);
+    for (n, out) in output[..input.len()].iter_mut().enumerate() {
+        *out = dot_product_f64(&reversed, &padded[n..n + taps.len()]);
+    }
+    Ok(())
+}
+
+/// Streaming f32 FIR filter that keeps its delay line between `process` calls,
+/// so consecutive chunks (e.g. 128-sample `AudioWorklet` blocks) filter exactly
+/// like one long buffer. Uses the SIMD dot product where available.
+#[wasm_bindgen]
+pub struct FirFilter {
+    /// Taps in reverse order, so each output is a contiguous dot product.
+    reversed: Vec<f32>,
+    /// Last `taps - 1` input samples.
+    history: Vec<f32>,
+    scratch: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl FirFilter {
+    /// Streaming filter with the given taps and a zeroed delay line.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidDimensions` if `taps` is empty.
+    #[wasm_bindgen(constructor)]
+    pub fn new(taps: &[f64]) -> Result<FirFilter, JsError> {
+        if taps.is_empty() {
+            return Err(WasmError::new(
+                ErrorCode::InvalidDimensions,
+                "FirFilter: needs at least one tap",
+            )
+            .into());
+        }
+        Ok(FirFilter {
+            reversed: taps.iter().rev().map(|&t| f64_to_f32(t)).collect(),
+            history: vec![0.0; taps.len() - 1],
+            scratch: Vec::new(),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn taps_len(&self) -> usize {
+        self.reversed.len()
+    }
+
+    /// Delay of a linear-phase filter in samples, `(taps - 1) / 2`.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn group_delay(&self) -> f64 {
+        usize_to_f64(self.history.len()) / 2.0
+    }
+
+    /// Clears the delay line.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+    }
+
+    /// Filters `input` into `output`, continuing from the previous call.
+    ///
+    /// # Errors
+    ///
+    /// `BufferTooSmall` if `output` is shorter than `input`.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) -> Result<(), JsError> {
+        require_len(output.len(), input.len(), "FirFilter.process: output")?;
+        self.load(input);
+        self.filter_into(&mut output[..input.len()]);
+        Ok(())
+    }
+
+    /// `process` with the output written over the input.
+    pub fn process_in_place(&mut self, data: &mut [f32]) {
+        self.load(data);
+        self.filter_into(data);
+    }
+}
+
+impl FirFilter {
+    fn load(&mut self, input: &[f32]) {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&self.history);
+        self.scratch.extend_from_slice(input);
+    }
+
+    fn filter_into(&mut self, output: &mut [f32]) {
+        let taps = self.reversed.len();
+        for (n, out) in output.iter_mut().enumerate() {
+            *out = dot_product_serial(&self.reversed, &self.scratch[n..n + taps]);
+        }
+        let keep = self.history.len();
+        self.history
+            .copy_from_slice(&self.scratch[self.scratch.len() - keep..]);
+    }
+}
+
+// ============================================================================
+// IIR FILTERS
+// ============================================================================
+
+/// RBJ "Audio EQ Cookbook" biquad shapes.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BiquadKind {
+    Lowpass = 0,
+    Highpass = 1,
+    /// Constant 0 dB peak gain.
+    Bandpass = 2,
+    Notch = 3,
+    /// Bell boost or cut of `gain_db` around the center frequency.
+    Peaking = 4,
+    LowShelf = 5,
+    HighShelf = 6,
+}
+
+/// Analog prototype for `BiquadCascade::design`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IirPrototype {
+    /// Maximally flat passband.
+    Butterworth = 0,
+    /// Equiripple passband (type I), steeper transition.
+    Chebyshev1 = 1,
+}
+
+/// Highest order `BiquadCascade::design` accepts.
+const IIR_MAX_ORDER: u32 = 32;
+
+/// One second-order section, normalized so `a0 = 1`.
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Biquad {
+    fn normalized(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+        }
+    }
+
+    /// Section from the RBJ cookbook formulas.
+    fn rbj(kind: BiquadKind, w0: f64, q: f64, gain_db: f64) -> Self {
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a = 10f64.powf(gain_db / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+        let (b, den) = match kind {
+            BiquadKind::Lowpass => (
+                [0.5 * (1.0 - cos), 1.0 - cos, 0.5 * (1.0 - cos)],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            BiquadKind::Highpass => (
+                [0.5 * (1.0 + cos), -(1.0 + cos), 0.5 * (1.0 + cos)],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            BiquadKind::Bandpass => ([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha]),
+            BiquadKind::Notch => (
+                [1.0, -2.0 * cos, 1.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            BiquadKind::Peaking => (
+                [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+                [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+            ),
+            BiquadKind::LowShelf => (
+                [
+                    a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                ],
+                [
+                    (a + 1.0) + (a - 1.0) * cos + shelf,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - shelf,
+                ],
+            ),
+            BiquadKind::HighShelf => (
+                [
+                    a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                ],
+                [
+                    (a + 1.0) - (a - 1.0) * cos + shelf,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - shelf,
+                ],
+            ),
+        };
+        Self::normalized(b, den)
+    }
+
+    /// Bilinear transform of the analog section `(B0 s^2 + B1 s + B2) / (A0 s^2 + A1 s + A2)`
+    /// with `s = c (1 - z^-1) / (1 + z^-1)`. First-order sections (`A0 = B0 = 0`)
+    /// stay first order.
+    fn bilinear(num: [f64; 3], den: [f64; 3], c: f64) -> Self {
+        let c2 = c * c;
+        if num[0] == 0.0 && den[0] == 0.0 {
+            return Self::normalized(
+                [num[1] * c + num[2], num[2] - num[1] * c, 0.0],
+                [den[1] * c + den[2], den[2] - den[1] * c, 0.0],
+            );
+        }
+        let map = |p: [f64; 3]| {
+            [
+                p[0] * c2 + p[1] * c + p[2],
+                2.0 * (p[2] - p[0] * c2),
+                p[0] * c2 - p[1] * c + p[2],
+            ]
+        };
+        Self::normalized(map(num), map(den))
+    }
+
+    /// Complex response at `z = e^{i omega}`.
+    fn response(&self, omega: f64) -> (f64, f64) {
+        let (s1, c1) = (-omega).sin_cos();
+        let (s2, c2) = (-2.0 * omega).sin_cos();
+        let num = (
+            self.b0 + self.b1 * c1 + self.b2 * c2,
+            self.b1 * s1 + self.b2 * s2,
+        );
+        let den = (
+            1.0 + self.a1 * c1 + self.a2 * c2,
+            self.a1 * s1 + self.a2 * s2,
+        );
+        let norm = den.0 * den.0 + den.1 * den.1;
+        (
+            (num.0 * den.0 + num.1 * den.1) / norm,
+            (num.1 * den.0 - num.0 * den.1) / norm,
+        )
+    }
+}
+
+/// Series of biquad sections in transposed direct form II. Sections come from
+/// the RBJ cookbook (`add_rbj`), raw coefficients (`add_section`) or a
+/// Butterworth / Chebyshev prototype of any order (`BiquadCascade.design`).
+/// Filter state persists across `process` calls until `reset`.
+#[wasm_bindgen]
+pub struct BiquadCascade {
+    sections: Vec<Biquad>,
+    /// `[z1, z2]` per section.
+    state: Vec<[f64; 2]>,
+}
+
+impl Default for BiquadCascade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl BiquadCascade {
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new() -> BiquadCascade {
+        BiquadCascade {
+            sections: Vec::new(),
+            state: Vec::new(),
+        }
+    }
+
+    /// Butterworth or Chebyshev type I lowpass/highpass of `order` (1..=32),
+    /// as `ceil(order / 2)` sections. `cutoff` is the -3 dB point for
+    /// Butterworth and the ripple band edge for Chebyshev, where
+    /// `ripple_db` (> 0) sets the passband ripple; Butterworth ignores it.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `sample_rate` is not positive, `cutoff` is outside
+    /// `(0, sample_rate / 2)`, `band` is bandpass or bandstop, `order` is outside
+    /// 1..=32, or a Chebyshev `ripple_db` is not positive.
+    pub fn design(
+        prototype: IirPrototype,
+        band: FilterBand,
+        order: u32,
+        sample_rate: f64,
+        cutoff: f64,
+        ripple_db: f64,
+    ) -> Result<BiquadCascade, JsError> {
+        let what = "BiquadCascade.design";
+        let w0 = normalized_frequency(sample_rate, cutoff, what)?;
+        if !matches!(band, FilterBand::Lowpass | FilterBand::Highpass) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: only lowpass and highpass prototypes are supported, got {band:?}"),
+            )
+            .into());
+        }
+        if !(1..=IIR_MAX_ORDER).contains(&order) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: order must be in 1..={IIR_MAX_ORDER}, got {order}"),
+            )
+            .into());
+        }
+        if prototype == IirPrototype::Chebyshev1 && !(ripple_db.is_finite() && ripple_db > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: Chebyshev ripple must be positive, got {ripple_db}"),
+            )
+            .into());
+        }
+
+        let mut cascade = BiquadCascade::new();
+        for section in prototype_sections(prototype, band, order, w0, ripple_db) {
+            cascade.push(section);
+        }
+        Ok(cascade)
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn section_count(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Appends an RBJ cookbook section. `q` sets the bandwidth (shelf slope for
+    /// shelves); `gain_db` only affects peaking and shelf sections.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `sample_rate` is not positive, `frequency` is outside
+    /// `(0, sample_rate / 2)`, `q` is not positive or `gain_db` is not finite.
+    pub fn add_rbj(
+        &mut self,
+        kind: BiquadKind,
+        sample_rate: f64,
+        frequency: f64,
+        q: f64,
+        gain_db: f64,
+    ) -> Result<(), JsError> {
+        let what = "BiquadCascade.add_rbj";
+        let w0 = normalized_frequency(sample_rate, frequency, what)?;
+        if !(q.is_finite() && q > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: Q must be positive, got {q}"),
+            )
+            .into());
+        }
+        require_finite(gain_db, what)?;
+        self.push(Biquad::rbj(kind, w0, q, gain_db));
+        Ok(())
+    }
+
+    /// Appends a section `(b0 + b1 z^-1 + b2 z^-2) / (a0 + a1 z^-1 + a2 z^-2)`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if a coefficient is not finite or `a0` is zero.
+    pub fn add_section(
+        &mut self,
+        b0: f64,
+        b1: f64,
+        b2: f64,
+        a0: f64,
+        a1: f64,
+        a2: f64,
+    ) -> Result<(), JsError> {
+        let what = "BiquadCascade.add_section";
+        for value in [b0, b1, b2, a0, a1, a2] {
+            require_finite(value, what)?;
+        }
+        if a0 == 0.0 {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: a0 must be nonzero"),
+            )
+            .into());
+        }
+        self.push(Biquad::normalized([b0, b1, b2], [a0, a1, a2]));
+        Ok(())
+    }
+
+    /// Clears the state of every section.
+    pub fn reset(&mut self) {
+        self.state.fill([0.0; 2]);
+    }
+
+    /// Filters `data` in place, continuing from the previous call.
+    pub fn process(&mut self, data: &mut [f64]) {
+        for (section, state) in self.sections.iter().zip(&mut self.state) {
+            for sample in data.iter_mut() {
+                *sample = biquad_step(section, state, *sample);
+            }
+        }
+    }
+
+    /// `process` for f32 buffers; the arithmetic and state stay f64.
+    pub fn process_f32(&mut self, data: &mut [f32]) {
+        for (section, state) in self.sections.iter().zip(&mut self.state) {
+            for sample in data.iter_mut() {
+                *sample = f64_to_f32(biquad_step(section, state, f64::from(*sample)));
+            }
+        }
+    }
+
+    /// Magnitude (linear) and phase (radians, in `[-pi, pi]`) of the cascade at
+    /// each frequency (Hz) in `frequencies`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `sample_rate` is not finite and positive, and
+    /// `BufferTooSmall` if `magnitude` or `phase` is shorter than `frequencies`.
+    pub fn frequency_response(
+        &self,
+        sample_rate: f64,
+        frequencies: &[f64],
+        magnitude: &mut [f64],
+        phase: &mut [f64],
+    ) -> Result<(), JsError> {
+        let what = "BiquadCascade.frequency_response";
+        if !(sample_rate.is_finite() && sample_rate > 0.0) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: sample rate must be positive, got {sample_rate}"),
+            )
+            .into());
+        }
+        require_len(
+            magnitude.len(),
+            frequencies.len(),
+            "BiquadCascade.frequency_response: magnitude",
+        )?;
+        require_len(
+            phase.len(),
+            frequencies.len(),
+            "BiquadCascade.frequency_response: phase",
+        )?;
+        for (i, &f) in frequencies.iter().enumerate() {
+            let omega = 2.0 * std::f64::consts::PI * f / sample_rate;
+            let (re, im) = self.sections.iter().fold((1.0, 0.0), |(re, im), section| {
+                let (sr, si) = section.response(omega);
+                (re * sr - im * si, re * si + im * sr)
+            });
+            magnitude[i] = re.hypot(im);
+            phase[i] = im.atan2(re);
+        }
+        Ok(())
+    }
+}
+
+impl BiquadCascade {
+    fn push(&mut self, section: Biquad) {
+        self.sections.push(section);
+        self.state.push([0.0; 2]);
+    }
+}
+
+fn biquad_step(section: &Biquad, state: &mut [f64; 2], x: f64) -> f64 {
+    let y = section.b0 * x + state[0];
+    state[0] = section.b1 * x - section.a1 * y + state[1];
+    state[1] = section.b2 * x - section.a2 * y;
+    y
+}
+
+/// Checks `frequency` against the sample rate and returns it in radians per sample.
+fn normalized_frequency(sample_rate: f64, frequency: f64, what: &str) -> Result<f64, WasmError> {
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("{what}: sample rate must be positive, got {sample_rate}"),
+        ));
+    }
+    if !(frequency.is_finite() && frequency > 0.0 && frequency < sample_rate / 2.0) {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "{what}: frequency must be in (0, {}) Hz, got {frequency}",
+                sample_rate / 2.0
+            ),
+        ));
+    }
+    Ok(2.0 * std::f64::consts::PI * frequency / sample_rate)
+}
+
+/// Digital sections of a Butterworth or Chebyshev type I lowpass/highpass.
+///
+/// The analog lowpass prototype with unit cutoff is built from its poles (one
+/// real pole for odd orders, conjugate pairs otherwise), turned into a
+/// highpass with `s -> 1/s` if needed, and mapped with the bilinear transform
+/// prewarped so the cutoff lands on `w0`.
+fn prototype_sections(
+    prototype: IirPrototype,
+    band: FilterBand,
+    order: u32,
+    w0: f64,
+    ripple_db: f64,
+) -> Vec<Biquad> {
+    use std::f64::consts::PI;
+    let n = f64::from(order);
+    let (sinh_mu, cosh_mu, passband_gain) = match prototype {
+        IirPrototype::Butterworth => (1.0, 1.0, 1.0),
+        IirPrototype::Chebyshev1 => {
+            let epsilon = (10f64.powf(ripple_db / 10.0) - 1.0).sqrt();
+            let mu = (1.0 / epsilon).asinh() / n;
+            // Even orders start the ripple at its bottom.
+            let gain = if order.is_multiple_of(2) {
+                1.0 / (1.0 + epsilon * epsilon).sqrt()
+            } else {
+                1.0
+            };
+            (mu.sinh(), mu.cosh(), gain)
+        }
+    };
+    let highpass = band == FilterBand::Highpass;
+    let c = 1.0 / (w0 / 2.0).tan();
+
+    let mut sections = Vec::new();
+    for k in 0..order / 2 {
+        let theta = PI * f64::from(2 * k + 1) / (2.0 * n);
+        let re = -sinh_mu * theta.sin();
+        let im = cosh_mu * theta.cos();
+        let mag2 = re * re + im * im;
+        // Unit gain at DC (lowpass) or infinity (highpass).
+        let (num, den) = if highpass {
+            ([1.0, 0.0, 0.0], [1.0, -2.0 * re / mag2, 1.0 / mag2])
+        } else {
+            ([0.0, 0.0, mag2], [1.0, -2.0 * re, mag2])
+        };
+        sections.push(Biquad::bilinear(num, den, c));
+    }
+    if !order.is_multiple_of(2) {
+        let pole = sinh_mu;
+        let (num, den) = if highpass {
+            ([0.0, 1.0, 0.0], [0.0, 1.0, 1.0 / pole])
+        } else {
+            ([0.0, 0.0, pole], [0.0, 1.0, pole])
+        };
+        sections.push(Biquad::bilinear(num, den, c));
+    }
+    if let Some(first) = sections.first_mut() {
+        first.b0 *= passband_gain;
+        first.b1 *= passband_gain;
+        first.b2 *= passband_gain;
+    }
+    sections
+}
+
+// ============================================================================
+// CONVOLUTION & CORRELATION
+// ============================================================================
+
+/// Kernels up to this length are convolved directly; longer ones use the FFT.
+const DIRECT_CONVOLUTION_MAX_LEN: usize = 64;
+/// Inputs at least this many times longer than the kernel use overlap-add
+/// instead of one large FFT.
+const OVERLAP_ADD_MIN_RATIO: usize = 8;
+
+/// Length of the full linear convolution of `a` and `b` (0 if either is empty).
+fn full_len(a: usize, b: usize) -> usize {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        a + b - 1
+    }
+}
+
+/// Half spectrum of `x` zero-padded to `n` (a power of two).
+fn padded_spectrum(x: &[f64], n: usize) -> Result<(Vec<f64>, Vec<f64>), WasmError> {
+    let mut padded = vec![0.0; n];
+    padded[..x.len()].copy_from_slice(x);
+    let bins = half_spectrum_len(n);
+    let mut re = vec![0.0; bins];
+    let mut im = vec![0.0; bins];
+    rfft_impl(&padded, &mut re, &mut im)?;
+    Ok((re, im))
+}
+
+/// Circular convolution of `x` (zero-padded to `n`) with the half spectrum
+/// `(h_re, h_im)` of a length-`n` kernel.
+fn convolve_spectrum(
+    x: &[f64],
+    h_re: &[f64],
+    h_im: &[f64],
+    n: usize,
+) -> Result<Vec<f64>, WasmError> {
+    let (mut re, mut im) = padded_spectrum(x, n)?;
+    for k in 0..re.len() {
+        let (xr, xi) = (re[k], im[k]);
+        re[k] = xr * h_re[k] - xi * h_im[k];
+        im[k] = xr * h_im[k] + xi * h_re[k];
+    }
+    let mut out = vec![0.0; n];
+    irfft_impl(&re, &im, &mut out)?;
+    Ok(out)
+}
+
+fn fft_size(len: usize, what: &str) -> Result<usize, WasmError> {
+    len.checked_next_power_of_two().ok_or_else(|| {
+        WasmError::new(
+            ErrorCode::SizeOverflow,
+            format!("{what}: FFT size for {len} samples overflows"),
+        )
+    })
+}
+
+/// Full linear convolution of `a` and `b` into `out[..a.len() + b.len() - 1]`.
+/// Picks direct summation, one FFT, or overlap-add from the input lengths.
+fn convolve_impl(a: &[f64], b: &[f64], out: &mut [f64], what: &str) -> Result<(), WasmError> {
+    let len = full_len(a.len(), b.len());
+    if len == 0 {
+        return Ok(());
+    }
+    let out = &mut out[..len];
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    if short.len() <= DIRECT_CONVOLUTION_MAX_LEN {
+        out.fill(0.0);
+        for (i, &x) in long.iter().enumerate() {
+            for (o, &h) in out[i..i + short.len()].iter_mut().zip(short) {
+                *o += x * h;
+            }
+        }
+        return Ok(());
+    }
+
+    if long.len() < OVERLAP_ADD_MIN_RATIO * short.len() {
+        let n = fft_size(len, what)?;
+        let (h_re, h_im) = padded_spectrum(short, n)?;
+        let full = convolve_spectrum(long, &h_re, &h_im, n)?;
+        out.copy_from_slice(&full[..len]);
+        return Ok(());
+    }
+
+    // Overlap-add: blocks of `step` samples, each convolved with one FFT of size n.
+    let n = fft_size(4 * short.len(), what)?;
+    let step = n - short.len() + 1;
+    let (h_re, h_im) = padded_spectrum(short, n)?;
+    out.fill(0.0);
+    for start in (0..long.len()).step_by(step) {
+        let block = &long[start..(start + step).min(long.len())];
+        let partial = convolve_spectrum(block, &h_re, &h_im, n)?;
+        let end = (start + n).min(len);
+        for (o, &y) in out[start..end].iter_mut().zip(&partial) {
+            *o += y;
+        }
+    }
+    Ok(())
+}
+
+/// Cross-correlation `r[lag] = sum_n a[n + lag] * b[n]` for every lag from
+/// `-(b.len() - 1)` to `a.len() - 1`, written to `out[lag + b.len() - 1]`.
+fn correlate_impl(a: &[f64], b: &[f64], out: &mut [f64], what: &str) -> Result<(), WasmError> {
+    let reversed: Vec<f64> = b.iter().rev().copied().collect();
+    convolve_impl(a, &reversed, out, what)
+}
+
+fn to_f64_vec(data: &[f32]) -> Vec<f64> {
+    data.iter().copied().map(f64::from).collect()
+}
+
+/// Full linear convolution; `out` needs `a.len() + b.len() - 1` elements.
+/// Short kernels are summed directly, long ones go through the FFT
+/// (overlap-add when one input is much longer than the other).
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `out` is too short, and `SizeOverflow` if the FFT size needed
+/// for the inputs overflows.
+#[wasm_bindgen]
+pub fn convolve(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), JsError> {
+    require_len(out.len(), full_len(a.len(), b.len()), "convolve: out")?;
+    convolve_impl(a, b, out, "convolve")?;
+    Ok(())
+}
+
+/// `convolve` for f32 buffers; the arithmetic runs in f64.
+///
+/// # Errors
+///
+/// As `convolve`.
+#[wasm_bindgen]
+pub fn convolve_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), JsError> {
+    let len = full_len(a.len(), b.len());
+    require_len(out.len(), len, "convolve_f32: out")?;
+    let mut result = vec![0.0; len];
+    convolve_impl(&to_f64_vec(a), &to_f64_vec(b), &mut result, "convolve_f32")?;
+    store_f32(&result, out);
+    Ok(())
+}
+
+/// Full cross-correlation `r[lag] = sum_n a[n + lag] * b[n]`. `out` needs
+/// `a.len() + b.len() - 1` elements; `out[i]` holds lag `i - (b.len() - 1)`,
+/// so zero lag sits at index `b.len() - 1`.
+///
+/// # Errors
+///
+/// As `convolve`.
+#[wasm_bindgen]
+pub fn correlate(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), JsError> {
+    require_len(out.len(), full_len(a.len(), b.len()), "correlate: out")?;
+    correlate_impl(a, b, out, "correlate")?;
+    Ok(())
+}
+
+/// `correlate` for f32 buffers; the arithmetic runs in f64.
+///
+/// # Errors
+///
+/// As `convolve`.
+#[wasm_bindgen]
+pub fn correlate_f32(a: &[f32], b: &[f32], out: &mut [f32]) -> Result<(), JsError> {
+    let len = full_len(a.len(), b.len());
+    require_len(out.len(), len, "correlate_f32: out")?;
+    let mut result = vec![0.0; len];
+    correlate_impl(&to_f64_vec(a), &to_f64_vec(b), &mut result, "correlate_f32")?;
+    store_f32(&result, out);
+    Ok(())
+}
+
+/// One-sided autocorrelation `r[lag] = sum_n x[n + lag] * x[n]` for lags
+/// `0..x.len()`.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `out` is shorter than `x`, and `SizeOverflow` if the FFT size
+/// overflows.
+#[wasm_bindgen]
+pub fn autocorrelate(x: &[f64], out: &mut [f64]) -> Result<(), JsError> {
+    require_len(out.len(), x.len(), "autocorrelate: out")?;
+    let mut full = vec![0.0; full_len(x.len(), x.len())];
+    correlate_impl(x, x, &mut full, "autocorrelate")?;
+    if !x.is_empty() {
+        out[..x.len()].copy_from_slice(&full[x.len() - 1..]);
+    }
+    Ok(())
+}
+
+/// Delay `d` (in samples, with parabolic sub-sample refinement) that best
+/// explains `signal[n] ~ reference[n - d]`, found at the cross-correlation peak.
+/// Only lags with `|d| <= max_lag` are searched.
+///
+/// # Errors
+///
+/// `InvalidDimensions` if either input is empty, and `SizeOverflow` if the FFT size
+/// overflows.
+#[wasm_bindgen]
+pub fn estimate_delay(reference: &[f64], signal: &[f64], max_lag: usize) -> Result<f64, JsError> {
+    if reference.is_empty() || signal.is_empty() {
+        return Err(WasmError::new(
+            ErrorCode::InvalidDimensions,
+            "estimate_delay: inputs must not be empty",
+        )
+        .into());
+    }
+    let mut r = vec![0.0; full_len(signal.len(), reference.len())];
+    correlate_impl(signal, reference, &mut r, "estimate_delay")?;
+
+    let zero = reference.len() - 1;
+    let lo = zero.saturating_sub(max_lag);
+    let hi = zero.saturating_add(max_lag).min(r.len() - 1);
+    let mut best = lo;
+    for i in lo..=hi {
+        if r[i] > r[best] {
+            best = i;
+        }
+    }
+    let offset = if best > lo && best < hi {
+        parabolic_offset(r[best - 1], r[best], r[best + 1])
+    } else {
+        0.0
+    };
+    Ok(usize_to_f64(best) - usize_to_f64(zero) + offset)
+}
+
+/// Vertex offset in `[-0.5, 0.5]` of the parabola through three equally spaced
+/// samples around a local maximum.
+fn parabolic_offset(left: f64, center: f64, right: f64) -> f64 {
+    let curvature = left - 2.0 * center + right;
+    if curvature == 0.0 {
+        0.0
+    } else {
+        (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+    }
+}
+
+// ============================================================================
+// PEAKS & PITCH
+// ============================================================================
+
+/// YIN accepts the first dip of the normalized difference below this value.
+const YIN_THRESHOLD: f64 = 0.15;
+/// Autocorrelation peaks below this fraction of the zero-lag energy count as
+/// unvoiced.
+const AUTOCORRELATION_VOICING: f64 = 0.3;
+/// Earliest autocorrelation peak within this fraction of the tallest is taken
+/// as the period.
+const AUTOCORRELATION_PEAK_RATIO: f64 = 0.9;
+
+/// Fundamental-frequency estimators for `estimate_pitch`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PitchMethod {
+    /// Cumulative-mean-normalized difference function (de Cheveigne & Kawahara).
+    Yin = 0,
+    /// Highest local maximum of the autocorrelation.
+    Autocorrelation = 1,
+}
+
+/// Indices of strict local maxima; a flat top counts once, at its middle.
+fn local_maxima(data: &[f64]) -> Vec<usize> {
+    let mut peaks = Vec::new();
+    let mut i = 1;
+    while i + 1 < data.len() {
+        if data[i - 1] < data[i] {
+            let mut end = i;
+            while end + 1 < data.len() && data[end + 1].total_cmp(&data[i]).is_eq() {
+                end += 1;
+            }
+            if end + 1 < data.len() && data[end + 1] < data[i] {
+                peaks.push(usize::midpoint(i, end));
+            }
+            i = end + 1;
+        } else {
+            i += 1;
+        }
+    }
+    peaks
+}
+
+/// Height of `data[peak]` above the higher of the lowest points reached on each
+/// side before meeting a taller sample (or the edge).
+fn prominence(data: &[f64], peak: usize) -> f64 {
+    let height = data[peak];
+    let mut left_base = height;
+    for &value in data[..peak].iter().rev() {
+        if value > height {
+            break;
+        }
+        left_base = left_base.min(value);
+    }
+    let mut right_base = height;
+    for &value in &data[peak + 1..] {
+        if value > height {
+            break;
+        }
+        right_base = right_base.min(value);
+    }
+    height - left_base.max(right_base)
+}
+
+/// Finds peaks in `data` (typically a magnitude spectrum), tallest first.
+/// Peaks need at least `min_prominence`; a peak closer than `min_distance`
+/// samples to a taller kept peak is dropped. Positions and heights are refined
+/// by parabolic interpolation, so positions are fractional bin indices.
+/// Writes up to `positions.len()` peaks and returns how many were written.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `min_prominence` is not finite, and `LengthMismatch` if
+/// `positions` and `heights` differ in length.
+#[wasm_bindgen]
+pub fn find_peaks(
+    data: &[f64],
+    min_prominence: f64,
+    min_distance: usize,
+    positions: &mut [f64],
+    heights: &mut [f64],
+) -> Result<usize, JsError> {
+    require_finite(min_prominence, "find_peaks: min_prominence")?;
+    if positions.len() != heights.len() {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!(
+                "find_peaks: positions ({}) and heights ({}) differ in length",
+                positions.len(),
+                heights.len()
+            ),
+        )
+        .into());
+    }
+
+    let mut candidates: Vec<usize> = local_maxima(data)
+        .into_iter()
+        .filter(|&peak| prominence(data, peak) >= min_prominence)
+        .collect();
+    candidates.sort_by(|&a, &b| data[b].total_cmp(&data[a]).then(a.cmp(&b)));
+
+    let mut kept: Vec<usize> = Vec::new();
+    for peak in candidates {
+        if kept.len() == positions.len() {
+            break;
+        }
+        if kept
+            .iter()
+            .all(|&other| peak.abs_diff(other) >= min_distance)
+        {
+            kept.push(peak);
+        }
+    }
+
+    for ((&peak, position), height) in kept.iter().zip(positions).zip(heights) {
+        let (left, center, right) = (data[peak - 1], data[peak], data[peak + 1]);
+        let offset = parabolic_offset(left, center, right);
+        *position = usize_to_f64(peak) + offset;
+        *height = center - 0.25 * (left - right) * offset;
+    }
+    Ok(kept.len())
+}
+
+/// Estimates the fundamental frequency of `signal` in Hz, searching
+/// `min_freq..=max_freq`. Returns 0 when no periodicity is found (silence,
+/// noise). The signal must hold more than `sample_rate / min_freq` samples.
+///
+/// # Errors
+///
+/// `InvalidArgument` unless the rates are finite with `0 < min_freq <= max_freq` and
+/// `sample_rate > 0`. `SizeOverflow` if the longest lag overflows `usize`,
+/// `BufferTooSmall` if `signal` is shorter than that lag plus 2, and `SizeOverflow` if
+/// the FFT size overflows.
+#[wasm_bindgen]
+pub fn estimate_pitch(
+    signal: &[f64],
+    sample_rate: f64,
+    min_freq: f64,
+    max_freq: f64,
+    method: PitchMethod,
+) -> Result<f64, JsError> {
+    require_finite(sample_rate, "estimate_pitch: sample_rate")?;
+    require_finite(min_freq, "estimate_pitch: min_freq")?;
+    require_finite(max_freq, "estimate_pitch: max_freq")?;
+    if sample_rate <= 0.0 || min_freq <= 0.0 || max_freq < min_freq {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "estimate_pitch: need 0 < min_freq <= max_freq and sample_rate > 0 \
+                 (got {min_freq}..{max_freq} Hz at {sample_rate} Hz)"
+            ),
+        )
+        .into());
+    }
+    let min_lag = f64_to_usize((sample_rate / max_freq).floor()).max(1);
+    let max_lag = f64_to_usize((sample_rate / min_freq).ceil());
+    let needed = max_lag.checked_add(2).ok_or_else(|| {
+        WasmError::new(
+            ErrorCode::SizeOverflow,
+            format!("estimate_pitch: min_freq {min_freq} Hz needs a lag beyond usize"),
+        )
+    })?;
+    require_len(signal.len(), needed, "estimate_pitch: signal")?;
+
+    let lag = match method {
+        PitchMethod::Yin => yin_lag(signal, min_lag, max_lag)?,
+        PitchMethod::Autocorrelation => autocorrelation_lag(signal, min_lag, max_lag)?,
+    };
+    Ok(lag.map_or(0.0, |lag| sample_rate / lag))
+}
+
+/// Period in samples from YIN over lags `min_lag..=max_lag`.
+fn yin_lag(signal: &[f64], min_lag: usize, max_lag: usize) -> Result<Option<f64>, WasmError> {
+    // d(tau) = sum_j (x[j] - x[j + tau])^2 over a window of `width` samples,
+    // expanded into energies and a cross term taken from one FFT correlation.
+    let width = signal.len() - max_lag - 1;
+    let mut cross = vec![0.0; full_len(signal.len(), width)];
+    correlate_impl(signal, &signal[..width], &mut cross, "estimate_pitch")?;
+    let mut prefix = Vec::with_capacity(signal.len() + 1);
+    prefix.push(0.0);
+    for &x in signal {
+        prefix.push(prefix[prefix.len() - 1] + x * x);
+    }
+    let energy = |start: usize| prefix[start + width] - prefix[start];
+
+    let difference: Vec<f64> = (0..max_lag + 2)
+        .map(|tau| (energy(0) + energy(tau) - 2.0 * cross[tau + width - 1]).max(0.0))
+        .collect();
+    let mut normalized = vec![1.0; max_lag + 2];
+    let mut running = 0.0;
+    for tau in 1..normalized.len() {
+        running += difference[tau];
+        if running > 0.0 {
+            normalized[tau] = difference[tau] * usize_to_f64(tau) / running;
+        }
+    }
+
+    let Some(mut tau) = (min_lag..=max_lag).find(|&tau| normalized[tau] < YIN_THRESHOLD) else {
+        return Ok(None);
+    };
+    while tau < max_lag && normalized[tau + 1] < normalized[tau] {
+        tau += 1;
+    }
+    // Refine on the raw difference, which is smoother than the normalized one.
+    let offset = parabolic_offset(-difference[tau - 1], -difference[tau], -difference[tau + 1]);
+    Ok(Some(usize_to_f64(tau) + offset))
+}
+
+/// Period in samples from the autocorrelation over lags `min_lag..=max_lag`:
+/// the earliest local maximum within `AUTOCORRELATION_PEAK_RATIO` of the
+/// tallest, so that multiples of the period do not win on rounding.
+fn autocorrelation_lag(
+    signal: &[f64],
+    min_lag: usize,
+    max_lag: usize,
+) -> Result<Option<f64>, WasmError> {
+    let mut full = vec![0.0; full_len(signal.len(), signal.len())];
+    correlate_impl(signal, signal, &mut full, "estimate_pitch")?;
+    let r = &full[signal.len() - 1..];
+    if r[0] <= 0.0 {
+        return Ok(None);
+    }
+    let maxima: Vec<usize> = local_maxima(&r[..=max_lag + 1])
+        .into_iter()
+        .filter(|&tau| tau >= min_lag)
+        .collect();
+    let Some(tallest) = maxima.iter().map(|&tau| r[tau]).max_by(f64::total_cmp) else {
+        return Ok(None);
+    };
+    if tallest < AUTOCORRELATION_VOICING * r[0] {
+        return Ok(None);
+    }
+    let best = maxima
+        .into_iter()
+        .find(|&tau| r[tau] >= AUTOCORRELATION_PEAK_RATIO * tallest)
+        .unwrap_or(min_lag);
+    Ok(Some(
+        usize_to_f64(best) + parabolic_offset(r[best - 1], r[best], r[best + 1]),
+    ))
+}
+
+// ============================================================================
+// RESAMPLING
+// ============================================================================
+
+/// Kernel half-width, in zero crossings of the anti-aliasing sinc.
+const RESAMPLE_ZERO_CROSSINGS: f64 = 32.0;
+/// Passband edge as a fraction of the lower of the two Nyquist frequencies.
+const RESAMPLE_ROLLOFF: f64 = 0.9;
+/// Kaiser beta of the kernel window.
+const RESAMPLE_KAISER_BETA: f64 = 9.0;
+/// Integer rates whose reduced ratio `up / down` has `up` above this use the
+/// interpolated table instead of exact polyphase rows.
+const RESAMPLE_MAX_PHASES: usize = 1024;
+/// Rows of the interpolated table used for arbitrary ratios.
+const RESAMPLE_TABLE_PHASES: usize = 512;
+/// Largest supported up- or downsampling factor.
+const RESAMPLE_MAX_RATIO: f64 = 64.0;
+
+#[derive(Clone, Copy, Debug)]
+enum ResampleStep {
+    /// Output `m` sits at input time `m * down / up`; `phase` counts `1 / up`.
+    Rational {
+        up: usize,
+        down: usize,
+        phase: usize,
+    },
+    /// `step` input samples per output, `frac` in `[0, 1)`.
+    Arbitrary { step: f64, frac: f64 },
+}
+
+impl ResampleStep {
+    /// Moves to the next output, returning how many whole input samples it crossed.
+    fn advance(&mut self) -> usize {
+        match self {
+            ResampleStep::Rational { up, down, phase } => {
+                *phase += *down;
+                let whole = *phase / *up;
+                *phase %= *up;
+                whole
+            }
+            ResampleStep::Arbitrary { step, frac } => {
+                *frac += *step;
+                let whole = frac.floor();
+                *frac -= whole;
+                f64_to_usize(whole)
+            }
+        }
+    }
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Kaiser-windowed sinc lowpass at `cutoff` cycles per input sample, evaluated
+/// `t` input samples from its centre; zero beyond `half_width`.
+fn resample_kernel(t: f64, cutoff: f64, half_width: f64) -> f64 {
+    use std::f64::consts::PI;
+    if t.abs() >= half_width {
+        return 0.0;
+    }
+    let x = 2.0 * cutoff * t;
+    let sinc = if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    };
+    let r = t / half_width;
+    let window =
+        bessel_i0(RESAMPLE_KAISER_BETA * (1.0 - r * r).sqrt()) / bessel_i0(RESAMPLE_KAISER_BETA);
+    2.0 * cutoff * sinc * window
+}
+
+/// Band-limited sample-rate converter. Integer rates with a small reduced
+/// ratio (48000 -> 16000 is 1/3, 44100 -> 48000 is 160/147) run as an exact
+/// polyphase FIR; other ratios interpolate between rows of a finely sampled
+/// windowed-sinc table. Streaming: `process` consumes chunks and returns what
+/// is ready, `flush` emits the tail, and the concatenated output equals
+/// one-shot `resample`.
+#[wasm_bindgen]
+pub struct Resampler {
+    input_rate: f64,
+    output_rate: f64,
+    taps: usize,
+    /// Coefficients, `taps` per phase row, each row summing to one.
+    table: Vec<f64>,
+    step: ResampleStep,
+    /// Unconsumed input, preceded by zeros standing in for the samples before
+    /// the stream started.
+    buffer: Vec<f64>,
+    /// Buffer index of the first tap of the next output.
+    next: usize,
+    /// Stream totals, in u64 so hours of audio do not wrap on wasm32.
+    consumed: u64,
+    produced: u64,
+}
+
+#[wasm_bindgen]
+impl Resampler {
+    /// Streaming resampler from `input_rate` to `output_rate` Hz.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if a rate is not finite and positive, or their ratio is
+    /// outside `[1/64, 64]`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(input_rate: f64, output_rate: f64) -> Result<Resampler, JsError> {
+        Ok(Resampler::build(input_rate, output_rate)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn input_rate(&self) -> f64 {
+        self.input_rate
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn output_rate(&self) -> f64 {
+        self.output_rate
+    }
+
+    /// Input samples each output is computed from.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn taps_len(&self) -> usize {
+        self.taps
+    }
+
+    /// Samples the next `process` call produces for `input_len` new inputs.
+    #[must_use]
+    pub fn output_len(&self, input_len: usize) -> usize {
+        let available = self.buffer.len() + input_len;
+        let mut step = self.step;
+        let mut next = self.next;
+        let mut count = 0;
+        while next + self.taps <= available {
+            count += 1;
+            next += step.advance();
+        }
+        count
+    }
+
+    /// Samples `flush` will write.
+    #[must_use]
+    pub fn flush_len(&self) -> usize {
+        let remaining = self.total_len(self.consumed).saturating_sub(self.produced);
+        usize::try_from(remaining).unwrap_or(usize::MAX)
+    }
+
+    /// Forgets all buffered input, as if freshly constructed.
+    pub fn reset(&mut self) {
+        let lead = self.lead();
+        self.buffer.clear();
+        self.buffer.resize(lead, 0.0);
+        self.next = 0;
+        self.consumed = 0;
+        self.produced = 0;
+        self.step = match self.step {
+            ResampleStep::Rational { up, down, .. } => {
+                ResampleStep::Rational { up, down, phase: 0 }
+            }
+            ResampleStep::Arbitrary { step, .. } => ResampleStep::Arbitrary { step, frac: 0.0 },
+        };
+    }
+
+    /// Consumes `input` and writes the outputs that are ready; `output` needs
+    /// `output_len(input.len())` elements. Returns the number written.
+    ///
+    /// # Errors
+    ///
+    /// `BufferTooSmall` if `output` is shorter than `output_len(input.len())`.
+    pub fn process(&mut self, input: &[f64], output: &mut [f64]) -> Result<usize, JsError> {
+        require_len(
+            output.len(),
+            self.output_len(input.len()),
+            "Resampler.process: output",
+        )?;
+        self.buffer.extend_from_slice(input);
+        self.consumed += input.len() as u64;
+        Ok(self.drain(output))
+    }
+
+    /// `process` for f32 buffers; filtering runs in f64.
+    ///
+    /// # Errors
+    ///
+    /// As `process`.
+    pub fn process_f32(&mut self, input: &[f32], output: &mut [f32]) -> Result<usize, JsError> {
+        let mut result = vec![0.0; self.output_len(input.len())];
+        require_len(output.len(), result.len(), "Resampler.process_f32: output")?;
+        let written = self.process(&to_f64_vec(input), &mut result)?;
+        store_f32(&result, output);
+        Ok(written)
+    }
+
+    /// Writes the remaining `flush_len()` outputs, treating the input as
+    /// followed by silence, then resets for a new stream.
+    ///
+    /// # Errors
+    ///
+    /// `BufferTooSmall` if `output` is shorter than `flush_len()`.
+    pub fn flush(&mut self, output: &mut [f64]) -> Result<usize, JsError> {
+        let remaining = self.flush_len();
+        require_len(output.len(), remaining, "Resampler.flush: output")?;
+        for out in &mut output[..remaining] {
+            if self.next + self.taps > self.buffer.len() {
+                self.buffer.resize(self.next + self.taps, 0.0);
+            }
+            *out = self.next_sample();
+        }
+        self.reset();
+        Ok(remaining)
+    }
+
+    /// `flush` for f32 buffers.
+    ///
+    /// # Errors
+    ///
+    /// As `flush`.
+    pub fn flush_f32(&mut self, output: &mut [f32]) -> Result<usize, JsError> {
+        let mut result = vec![0.0; self.flush_len()];
+        require_len(output.len(), result.len(), "Resampler.flush_f32: output")?;
+        let written = self.flush(&mut result)?;
+        store_f32(&result, output);
+        Ok(written)
+    }
+}
+
+impl Resampler {
+    fn build(input_rate: f64, output_rate: f64) -> Result<Resampler, WasmError> {
+        for (rate, what) in [(input_rate, "input"), (output_rate, "output")] {
+            if !(rate.is_finite() && rate > 0.0) {
+                return Err(WasmError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("Resampler: {what} rate must be positive, got {rate}"),
+                ));
+            }
+        }
+        let ratio = output_rate / input_rate;
+        if !(1.0 / RESAMPLE_MAX_RATIO..=RESAMPLE_MAX_RATIO).contains(&ratio) {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "Resampler: ratio {ratio} is outside [1/{RESAMPLE_MAX_RATIO}, \
+                     {RESAMPLE_MAX_RATIO}]"
+                ),
+            ));
+        }
+
+        // Equal rates pass samples straight through a single-tap row.
+        let passthrough = input_rate.total_cmp(&output_rate).is_eq();
+        let step = if passthrough {
+            ResampleStep::Rational {
+                up: 1,
+                down: 1,
+                phase: 0,
+            }
+        } else {
+            Resampler::rational_step(input_rate, output_rate).unwrap_or(ResampleStep::Arbitrary {
+                step: input_rate / output_rate,
+                frac: 0.0,
+            })
+        };
+        let (rows, phases) = match step {
+            ResampleStep::Rational { up, .. } => (up, up),
+            ResampleStep::Arbitrary { .. } => (RESAMPLE_TABLE_PHASES + 1, RESAMPLE_TABLE_PHASES),
+        };
+
+        let (taps, table) = if passthrough {
+            (2, vec![1.0, 0.0])
+        } else {
+            let cutoff = 0.5 * ratio.min(1.0) * RESAMPLE_ROLLOFF;
+            let half_width = RESAMPLE_ZERO_CROSSINGS / (2.0 * cutoff);
+            let lead = f64_to_usize(half_width.ceil());
+            let taps = 2 * lead + 2;
+            let mut table = vec![0.0; rows * taps];
+            for (row, coefficients) in table.chunks_exact_mut(taps).enumerate() {
+                let frac = usize_to_f64(row) / usize_to_f64(phases);
+                for (k, c) in coefficients.iter_mut().enumerate() {
+                    *c = resample_kernel(
+                        frac + usize_to_f64(lead) - usize_to_f64(k),
+                        cutoff,
+                        half_width,
+                    );
+                }
+                let sum: f64 = coefficients.iter().sum();
+                for c in coefficients.iter_mut() {
+                    *c /= sum;
+                }
+            }
+            (taps, table)
+        };
+
+        let mut resampler = Resampler {
+            input_rate,
+            output_rate,
+            taps,
+            table,
+            step,
+            buffer: Vec::new(),
+            next: 0,
+            consumed: 0,
+            produced: 0,
+        };
+        resampler.reset();
+        Ok(resampler)
+    }
+
+    /// Exact `up / down` stepping when both rates are whole numbers and the
+    /// reduced ratio needs at most `RESAMPLE_MAX_PHASES` rows.
+    fn rational_step(input_rate: f64, output_rate: f64) -> Option<ResampleStep> {
+        let whole = |rate: f64| (rate.fract() == 0.0).then(|| f64_to_usize(rate));
+        let (input, output) = (whole(input_rate)?, whole(output_rate)?);
+        let divisor = gcd(input, output);
+        let (up, down) = (output / divisor, input / divisor);
+        (up <= RESAMPLE_MAX_PHASES).then_some(ResampleStep::Rational { up, down, phase: 0 })
+    }
+
+    /// Samples before the first input that the kernel reaches back to.
+    fn lead(&self) -> usize {
+        self.taps / 2 - 1
+    }
+
+    /// Output samples a stream of `input_len` inputs yields in all: every output
+    /// whose position falls before the end of the input. The rational product
+    /// is taken in u128 so long inputs cannot overflow.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn total_len(&self, input_len: u64) -> u64 {
+        match self.step {
+            ResampleStep::Rational { up, down, .. } => {
+                let total = (u128::from(input_len) * up as u128).div_ceil(down as u128);
+                u64::try_from(total).unwrap_or(u64::MAX)
+            }
+            ResampleStep::Arbitrary { step, .. } => (input_len as f64 / step).ceil() as u64,
+        }
+    }
+
+    /// `total_len` for an in-memory input, as a buffer length.
+    fn output_total(&self, input_len: usize, what: &str) -> Result<usize, WasmError> {
+        usize::try_from(self.total_len(input_len as u64)).map_err(|_| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                format!("{what}: output for {input_len} samples does not fit in memory"),
+            )
+        })
+    }
+
+    /// Computes the next output from `buffer[next..next + taps]` and advances.
+    fn next_sample(&mut self) -> f64 {
+        let window = &self.buffer[self.next..self.next + self.taps];
+        let row = |index: usize| &self.table[index * self.taps..(index + 1) * self.taps];
+        let value = match self.step {
+            ResampleStep::Rational { phase, .. } => dot_product_f64(window, row(phase)),
+            ResampleStep::Arbitrary { frac, .. } => {
+                let position = frac * usize_to_f64(RESAMPLE_TABLE_PHASES);
+                let index = f64_to_usize(position.floor()).min(RESAMPLE_TABLE_PHASES - 1);
+                let weight = position - usize_to_f64(index);
+                let lower = dot_product_f64(window, row(index));
+                let upper = dot_product_f64(window, row(index + 1));
+                lower + weight * (upper - lower)
+            }
+        };
+        self.next += self.step.advance();
+        self.produced += 1;
+        value
+    }
+
+    /// Writes every output whose window is buffered, then drops consumed input.
+    fn drain(&mut self, output: &mut [f64]) -> usize {
+        let mut written = 0;
+        while self.next + self.taps <= self.buffer.len() {
+            output[written] = self.next_sample();
+            written += 1;
+        }
+        let consumed = self.next.min(self.buffer.len());
+        self.buffer.drain(..consumed);
+        self.next -= consumed;
+        written
+    }
+}
+
+/// Length of `resample` output for `input_len` samples: `ceil(input_len *
+/// output_rate / input_rate)`.
+///
+/// # Errors
+///
+/// `InvalidArgument` for rates `Resampler::new` rejects, and `SizeOverflow` if the
+/// length does not fit in `usize`.
+#[wasm_bindgen]
+pub fn resampled_len(
+    input_len: usize,
+    input_rate: f64,
+    output_rate: f64,
+) -> Result<usize, JsError> {
+    Ok(Resampler::build(input_rate, output_rate)?.output_total(input_len, "resampled_len")?)
+}
+
+/// One-shot band-limited resampling of `input` from `input_rate` to
+/// `output_rate`; `output` needs `resampled_len(...)` elements. Returns the
+/// number written.
+///
+/// # Errors
+///
+/// As `resampled_len`, plus `BufferTooSmall` if `output` is shorter than that length.
+#[wasm_bindgen]
+pub fn resample(
+    input: &[f64],
+    input_rate: f64,
+    output_rate: f64,
+    output: &mut [f64],
+) -> Result<usize, JsError> {
+    let mut resampler = Resampler::build(input_rate, output_rate)?;
+    let len = resampler.output_total(input.len(), "resample")?;
+    require_len(output.len(), len, "resample: output")?;
+    let written = resampler.process(input, output)?;
+    resampler.flush(&mut output[written..])?;
+    Ok(len)
+}
+
+/// `resample` for f32 buffers; filtering runs in f64.
+///
+/// # Errors
+///
+/// As `resample`.
+#[wasm_bindgen]
+pub fn resample_f32(
+    input: &[f32],
+    input_rate: f64,
+    output_rate: f64,
+    output: &mut [f32],
+) -> Result<usize, JsError> {
+    let mut result = vec![0.0; resampled_len(input.len(), input_rate, output_rate)?];
+    require_len(output.len(), result.len(), "resample_f32: output")?;
+    let written = resample(&to_f64_vec(input), input_rate, output_rate, &mut result)?;
+    store_f32(&result, output);
+    Ok(written)
+}
+
+// ============================================================================
+// WAV AUDIO
+// ============================================================================
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Sample encodings read by `decode_wav` and written by `encode_wav`. PCM
+/// samples map to `[-1, 1)` (8-bit is unsigned, the rest signed); float
+/// samples are stored as-is.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WavFormat {
+    Pcm8 = 0,
+    Pcm16 = 1,
+    Pcm24 = 2,
+    Pcm32 = 3,
+    Float32 = 4,
+    Float64 = 5,
+}
+
+impl WavFormat {
+    fn bytes_per_sample(self) -> usize {
+        match self {
+            WavFormat::Pcm8 => 1,
+            WavFormat::Pcm16 => 2,
+            WavFormat::Pcm24 => 3,
+            WavFormat::Pcm32 | WavFormat::Float32 => 4,
+            WavFormat::Float64 => 8,
+        }
+    }
+
+    fn tag(self) -> u16 {
+        match self {
+            WavFormat::Float32 | WavFormat::Float64 => WAVE_FORMAT_IEEE_FLOAT,
+            _ => WAVE_FORMAT_PCM,
+        }
+    }
+
+    /// Full scale of the integer encodings.
+    fn scale(self) -> f64 {
+        match self {
+            WavFormat::Pcm8 => 128.0,
+            WavFormat::Pcm16 => 32_768.0,
+            WavFormat::Pcm24 => 8_388_608.0,
+            WavFormat::Pcm32 => 2_147_483_648.0,
+            WavFormat::Float32 | WavFormat::Float64 => 1.0,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f64 {
+        match self {
+            WavFormat::Pcm8 => (f64::from(bytes[0]) - 128.0) / 128.0,
+            WavFormat::Pcm16 => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])) / self.scale(),
+            // Shift the sample into the top bytes so the sign extends.
+            WavFormat::Pcm24 => {
+                f64::from(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) / self.scale()
+            }
+            WavFormat::Pcm32 => {
+                f64::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    / self.scale()
+            }
+            WavFormat::Float32 => {
+                f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            WavFormat::Float64 => {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(&bytes[..8]);
+                f64::from_le_bytes(raw)
+            }
+        }
+    }
+
+    fn encode(self, value: f64, out: &mut Vec<u8>) {
+        match self {
+            WavFormat::Pcm8 => out.push((quantize(value, self.scale()) + 128).to_le_bytes()[0]),
+            WavFormat::Pcm16 | WavFormat::Pcm24 | WavFormat::Pcm32 => {
+                let bytes = quantize(value, self.scale()).to_le_bytes();
+                out.extend_from_slice(&bytes[..self.bytes_per_sample()]);
+            }
+            WavFormat::Float32 => out.extend_from_slice(&f64_to_f32(value).to_le_bytes()),
+            WavFormat::Float64 => out.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+}
+
+/// `value * scale` rounded and clipped to the signed range `[-scale, scale)`.
+#[allow(clippy::cast_possible_truncation)]
+fn quantize(value: f64, scale: f64) -> i32 {
+    (value * scale).round().clamp(-scale, scale - 1.0) as i32
 }
 
-#[wasm_bindgen]
-pub fn box_blur(data: &mut [u8], width: u32, height: u32, radius: u32) {
-    let w = usize::try_from(width).unwrap_or(usize::MAX);
-    let h = usize::try_from(height).unwrap_or(usize::MAX);
-    let r = usize::try_from(radius).unwrap_or(usize::MAX);
-    if w == 0 || h == 0 {
-        return;
+fn wav_error(message: impl Into<String>) -> WasmError {
+    WasmError::new(ErrorCode::InvalidFormat, message)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Decoded `fmt ` chunk.
+struct WavHeader {
+    format: WavFormat,
+    channels: usize,
+    sample_rate: u32,
+}
+
+fn parse_wav_fmt(chunk: &[u8]) -> Result<WavHeader, WasmError> {
+    if chunk.len() < 16 {
+        return Err(wav_error(format!(
+            "decode_wav: fmt chunk has {} bytes, expected at least 16",
+            chunk.len()
+        )));
+    }
+    let mut tag = read_u16(chunk, 0);
+    let channels = usize::from(read_u16(chunk, 2));
+    let sample_rate = read_u32(chunk, 4);
+    let block_align = usize::from(read_u16(chunk, 12));
+    let bits = read_u16(chunk, 14);
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        // The sub-format GUID at byte 24 starts with the plain format tag.
+        if chunk.len() < 40 {
+            return Err(wav_error(format!(
+                "decode_wav: extensible fmt chunk has {} bytes, expected 40",
+                chunk.len()
+            )));
+        }
+        tag = read_u16(chunk, 24);
     }
 
-    let Some(pixel_bytes) = w.checked_mul(h).and_then(|pixels| pixels.checked_mul(4)) else {
-        return;
+    let format = match (tag, bits) {
+        (WAVE_FORMAT_PCM, 8) => WavFormat::Pcm8,
+        (WAVE_FORMAT_PCM, 16) => WavFormat::Pcm16,
+        (WAVE_FORMAT_PCM, 24) => WavFormat::Pcm24,
+        (WAVE_FORMAT_PCM, 32) => WavFormat::Pcm32,
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => WavFormat::Float32,
+        (WAVE_FORMAT_IEEE_FLOAT, 64) => WavFormat::Float64,
+        _ => {
+            return Err(wav_error(format!(
+                "decode_wav: unsupported encoding (format tag {tag}, {bits} bits)"
+            )))
+        }
     };
-    if data.len() < pixel_bytes {
-        return;
+    if channels == 0 || sample_rate == 0 {
+        return Err(wav_error(format!(
+            "decode_wav: {channels} channels at {sample_rate} Hz"
+        )));
     }
+    if block_align != channels * format.bytes_per_sample() {
+        return Err(wav_error(format!(
+            "decode_wav: block align {block_align} does not match {channels} x {bits}-bit samples"
+        )));
+    }
+    Ok(WavHeader {
+        format,
+        channels,
+        sample_rate,
+    })
+}
 
-    let mut output = data.to_vec();
+/// Audio decoded by `decode_wav`, one f32 buffer per channel.
+#[wasm_bindgen]
+pub struct WavAudio {
+    sample_rate: u32,
+    format: WavFormat,
+    channels: Vec<Vec<f32>>,
+}
 
-    for y in 0..h {
-        let y_start = y.saturating_sub(r);
-        let y_end = (y + r).min(h - 1);
-        for x in 0..w {
-            let x_start = x.saturating_sub(r);
-            let x_end = (x + r).min(w - 1);
-            let mut sum_r = 0u32;
-            let mut sum_g = 0u32;
-            let mut sum_b = 0u32;
-            let mut count = 0u32;
-
-            for ny in y_start..=y_end {
-                for nx in x_start..=x_end {
-                    let idx = (ny * w + nx) * 4;
-                    sum_r += u32::from(data[idx]);
-                    sum_g += u32::from(data[idx + 1]);
-                    sum_b += u32::from(data[idx + 2]);
-                    count += 1;
-                }
-            }
+#[wasm_bindgen]
+impl WavAudio {
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
 
-            let idx = (y * w + x) * 4;
-            let avg_r = sum_r / count;
-            let avg_g = sum_g / count;
-            let avg_b = sum_b / count;
+    /// Encoding the file was stored in.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn format(&self) -> WavFormat {
+        self.format
+    }
 
-            output[idx] = u8::try_from(avg_r).unwrap_or(u8::MAX);
-            output[idx + 1] = u8::try_from(avg_g).unwrap_or(u8::MAX);
-            output[idx + 2] = u8::try_from(avg_b).unwrap_or(u8::MAX);
-            output[idx + 3] = data[idx + 3];
-        }
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
     }
 
-    data.copy_from_slice(&output);
-}
+    /// Samples per channel.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
 
-// ============================================================================
-// FFT & SIGNAL PROCESSING
-// ============================================================================
+    /// Copy of channel `index`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` if `index` is not below the channel count.
+    pub fn channel_data(&self, index: usize) -> Result<Vec<f32>, JsError> {
+        let Some(channel) = self.channels.get(index) else {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "WavAudio.channel_data: channel {index} of {}",
+                    self.channels.len()
+                ),
+            )
+            .into());
+        };
+        Ok(channel.clone())
+    }
+}
 
+/// Parses a RIFF/WAVE file holding 8/16/24/32-bit PCM or 32/64-bit float
+/// samples (plain or `WAVE_FORMAT_EXTENSIBLE`). Unknown chunks are skipped; a
+/// trailing partial frame is dropped. A data size of `0xFFFFFFFF` (streamed
+/// writers) means "to the end of the file".
+///
+/// # Errors
+///
+/// `InvalidFormat` if `bytes` is not a RIFF/WAVE file, a chunk overruns the file, the
+/// `fmt ` chunk is missing, comes after `data` or describes an unsupported encoding, or
+/// there is no `data` chunk.
 #[wasm_bindgen]
-pub fn fft_demo(input: &[f64], output: &mut [f64]) {
-    let n = input.len().min(output.len());
-    if n == 0 {
-        return;
+pub fn decode_wav(bytes: &[u8]) -> Result<WavAudio, JsError> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(wav_error("decode_wav: not a RIFF/WAVE file").into());
     }
+    let mut header = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let declared = read_u32(bytes, offset + 4);
+        let body = offset + 8;
+        let available = bytes.len() - body;
+        let size = match usize::try_from(declared) {
+            _ if id == b"data" && declared == u32::MAX => available,
+            Ok(size) if size <= available => size,
+            _ => {
+                return Err(wav_error(format!(
+                    "decode_wav: '{}' chunk declares {declared} bytes but only {available} remain",
+                    String::from_utf8_lossy(id)
+                ))
+                .into())
+            }
+        };
+        let chunk = &bytes[body..body + size];
 
-    let n_f64 = usize_to_f64(n);
-    for (k, out) in output.iter_mut().take(n).enumerate() {
-        let k_f64 = usize_to_f64(k);
-        let mut sum = 0.0;
-        for (t, &sample) in input.iter().take(n).enumerate() {
-            let angle = 2.0 * std::f64::consts::PI * k_f64 * usize_to_f64(t) / n_f64;
-            sum += sample * angle.cos();
+        if id == b"fmt " {
+            header = Some(parse_wav_fmt(chunk)?);
+        } else if id == b"data" {
+            let Some(WavHeader {
+                format,
+                channels,
+                sample_rate,
+            }) = header
+            else {
+                return Err(wav_error("decode_wav: data chunk precedes the fmt chunk").into());
+            };
+            let width = format.bytes_per_sample();
+            let frames = size / (width * channels);
+            let mut buffers = vec![Vec::with_capacity(frames); channels];
+            for frame in chunk.chunks_exact(width * channels) {
+                for (buffer, sample) in buffers.iter_mut().zip(frame.chunks_exact(width)) {
+                    buffer.push(f64_to_f32(format.decode(sample)));
+                }
+            }
+            return Ok(WavAudio {
+                sample_rate,
+                format,
+                channels: buffers,
+            });
         }
-        *out = sum.abs();
+        // Chunks are padded to an even length.
+        offset = body + size + (size & 1);
     }
+    Err(wav_error("decode_wav: no data chunk").into())
 }
 
-#[wasm_bindgen]
-pub fn generate_signal(buffer: &mut [f64], freq1: f64, freq2: f64, freq3: f64) {
-    let n = buffer.len();
-    if n == 0 {
-        return;
+fn encode_wav_impl(
+    planar: &[f64],
+    channels: usize,
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<Vec<u8>, WasmError> {
+    let Ok(channel_field) = u16::try_from(channels) else {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("encode_wav: {channels} channels exceeds the format limit"),
+        ));
+    };
+    if channels == 0 || sample_rate == 0 {
+        return Err(WasmError::new(
+            ErrorCode::InvalidArgument,
+            format!("encode_wav: {channels} channels at {sample_rate} Hz"),
+        ));
+    }
+    if !planar.len().is_multiple_of(channels) {
+        return Err(WasmError::new(
+            ErrorCode::LengthMismatch,
+            format!(
+                "encode_wav: {} samples do not split into {channels} channels",
+                planar.len()
+            ),
+        ));
     }
+    let frames = planar.len() / channels;
+    let width = format.bytes_per_sample();
+    let overflow = || {
+        WasmError::new(
+            ErrorCode::SizeOverflow,
+            format!("encode_wav: {frames} frames exceed the 4 GiB WAV limit"),
+        )
+    };
+    let data_len = planar.len().checked_mul(width).ok_or_else(overflow)?;
+    let padded = data_len + (data_len & 1);
+    let data_size = u32::try_from(data_len).map_err(|_| overflow())?;
+    let riff_size = u32::try_from(padded + 36).map_err(|_| overflow())?;
+    let block_align = u16::try_from(channels * width).map_err(|_| overflow())?;
+    let byte_rate = u32::from(block_align)
+        .checked_mul(sample_rate)
+        .ok_or_else(overflow)?;
+    let bits = block_align / channel_field * 8;
 
-    let n_f64 = usize_to_f64(n);
-    for (i, sample) in buffer.iter_mut().enumerate() {
-        let t = usize_to_f64(i) / n_f64;
-        *sample = (2.0 * std::f64::consts::PI * freq1 * t).sin()
-            + 0.5 * (2.0 * std::f64::consts::PI * freq2 * t).sin()
-            + 0.3 * (2.0 * std::f64::consts::PI * freq3 * t).sin();
+    let mut out = Vec::with_capacity(44 + padded);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&riff_size.to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&format.tag().to_le_bytes());
+    out.extend_from_slice(&channel_field.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&byte_rate.to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_size.to_le_bytes());
+    for frame in 0..frames {
+        for channel in 0..channels {
+            format.encode(planar[channel * frames + frame], &mut out);
+        }
     }
+    out.resize(44 + padded, 0);
+    Ok(out)
 }
 
-fn usize_to_f64(value: usize) -> f64 {
-    let Ok(converted) = u32::try_from(value) else {
-        return f64::from(u32::MAX);
-    };
-    f64::from(converted)
+/// Encodes planar samples (channel 0 first, then channel 1, ...; each
+/// `planar.len() / channels` long) as a canonical 44-byte-header WAV file.
+/// PCM output rounds and clips to the integer range.
+///
+/// # Errors
+///
+/// `InvalidArgument` if `channels` or `sample_rate` is zero or `channels` exceeds
+/// 65535, `LengthMismatch` if `planar.len()` is not a multiple of `channels`, and
+/// `SizeOverflow` if the file would exceed the 4 GiB WAV limit.
+#[wasm_bindgen]
+pub fn encode_wav(
+    planar: &[f64],
+    channels: usize,
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<Vec<u8>, JsError> {
+    Ok(encode_wav_impl(planar, channels, sample_rate, format)?)
+}
+
+/// `encode_wav` for f32 buffers.
+///
+/// # Errors
+///
+/// As `encode_wav`.
+#[wasm_bindgen]
+pub fn encode_wav_f32(
+    planar: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<Vec<u8>, JsError> {
+    Ok(encode_wav_impl(
+        &to_f64_vec(planar),
+        channels,
+        sample_rate,
+        format,
+    )?)
 }
 
 // ============================================================================
@@ -502,89 +7068,283 @@ impl Workspace {
     }
 }
 
-fn matrix_multiply_naive(a: &[f64], b: &[f64], c: &mut [f64], n: usize) {
-    for i in 0..n {
-        for j in 0..n {
-            let mut sum = 0.0;
-            for k in 0..n {
-                sum += a[i * n + k] * b[k * n + j];
-            }
-            c[i * n + j] = sum;
+fn matrix_multiply_naive(
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+    n: usize,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
+    let c = &mut c[..n * n];
+
+    #[cfg(feature = "parallel")]
+    if n * n >= PARALLEL_MIN_LEN {
+        return c.par_chunks_mut(n).enumerate().try_for_each(|(i, row)| {
+            matrix_multiply_naive_row(a, b, row, n, i);
+            checkpoint(control, 1)
+        });
+    }
+
+    for (i, row) in c.chunks_mut(n).enumerate() {
+        matrix_multiply_naive_row(a, b, row, n, i);
+        checkpoint(control, 1)?;
+    }
+    Ok(())
+}
+
+fn matrix_multiply_naive_row(a: &[f64], b: &[f64], row: &mut [f64], n: usize, i: usize) {
+    for (j, out) in row.iter_mut().enumerate() {
+        let mut sum = 0.0;
+        for k in 0..n {
+            sum += a[i * n + k] * b[k * n + j];
         }
+        *out = sum;
     }
 }
 
-fn matrix_multiply_blocked(a: &[f64], b: &[f64], c: &mut [f64], n: usize, block: usize) {
-    c.fill(0.0);
+fn matrix_multiply_blocked(
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+    n: usize,
+    block: usize,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
     let bs = block.max(1);
-    let mut ii = 0;
-    while ii < n {
-        let i_max = (ii + bs).min(n);
-        let mut kk = 0;
-        while kk < n {
-            let k_max = (kk + bs).min(n);
-            let mut jj = 0;
-            while jj < n {
-                let j_max = (jj + bs).min(n);
-                for i in ii..i_max {
-                    for k in kk..k_max {
-                        let a_ik = a[i * n + k];
-                        let row = i * n;
-                        let col = k * n;
-                        for j in jj..j_max {
-                            c[row + j] += a_ik * b[col + j];
-                        }
+    let c = &mut c[..n * n];
+
+    // Each band of `bs` rows of C is independent, so bands are the unit of work.
+    #[cfg(feature = "parallel")]
+    {
+        c.par_chunks_mut(bs * n)
+            .enumerate()
+            .try_for_each(|(band, rows)| {
+                matrix_multiply_blocked_band(a, b, rows, n, bs, band * bs);
+                checkpoint(control, rows.len() / n)
+            })
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        for (band, rows) in c.chunks_mut(bs * n).enumerate() {
+            matrix_multiply_blocked_band(a, b, rows, n, bs, band * bs);
+            checkpoint(control, rows.len() / n)?;
+        }
+        Ok(())
+    }
+}
+
+fn matrix_multiply_blocked_band(
+    a: &[f64],
+    b: &[f64],
+    rows: &mut [f64],
+    n: usize,
+    bs: usize,
+    ii: usize,
+) {
+    rows.fill(0.0);
+    let i_max = ii + rows.len() / n;
+    let mut kk = 0;
+    while kk < n {
+        let k_max = (kk + bs).min(n);
+        let mut jj = 0;
+        while jj < n {
+            let j_max = (jj + bs).min(n);
+            for i in ii..i_max {
+                let row = (i - ii) * n;
+                for k in kk..k_max {
+                    let a_ik = a[i * n + k];
+                    let col = k * n;
+                    for j in jj..j_max {
+                        rows[row + j] += a_ik * b[col + j];
                     }
                 }
-                jj += bs;
             }
-            kk += bs;
+            jj += bs;
+        }
+        kk += bs;
+    }
+}
+
+/// Element count of an `n` x `n` matrix, rejecting sizes whose `n * n` values
+/// cannot be addressed.
+fn matrix_len(n: usize, what: &str) -> Result<usize, WasmError> {
+    n.checked_mul(n)
+        .filter(|size| {
+            size.checked_mul(std::mem::size_of::<f64>())
+                .is_some_and(|bytes| isize::try_from(bytes).is_ok())
+        })
+        .ok_or_else(|| {
+            WasmError::new(
+                ErrorCode::SizeOverflow,
+                format!("{what}: n = {n} overflows n * n"),
+            )
+        })
+}
+
+/// Checks that `a`, `b` and `c` each hold at least `n * n` values.
+fn check_matrix_operands(
+    a: &[f64],
+    b: &[f64],
+    c: &[f64],
+    n: usize,
+    what: &str,
+) -> Result<(), WasmError> {
+    let size = matrix_len(n, what)?;
+    require_len(a.len(), size, &format!("{what}: a"))?;
+    require_len(b.len(), size, &format!("{what}: b"))?;
+    require_len(c.len(), size, &format!("{what}: c"))?;
+    Ok(())
+}
+
+/// Validates raw matrix pointers from JS and returns `n * n`.
+///
+/// Each pointer must be non-null and `f64`-aligned, and the output matrix must not
+/// overlap either input since the kernels read `a` and `b` while writing `c`.
+fn check_matrix_ptrs(
+    a_ptr: *const f64,
+    b_ptr: *const f64,
+    c_ptr: *mut f64,
+    n: usize,
+    what: &str,
+) -> Result<usize, WasmError> {
+    let size = matrix_len(n, what)?;
+    let c_ptr = c_ptr.cast_const();
+    for (name, ptr) in [("a_ptr", a_ptr), ("b_ptr", b_ptr), ("c_ptr", c_ptr)] {
+        require_non_null(ptr, size, &format!("{what}: {name}"))?;
+        if !ptr.is_aligned() {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: {name} is not 8-byte aligned"),
+            ));
+        }
+    }
+    let span = |ptr: *const f64| {
+        let start = ptr as usize;
+        start..start.saturating_add(size * std::mem::size_of::<f64>())
+    };
+    let c_span = span(c_ptr);
+    for (name, ptr) in [("a_ptr", a_ptr), ("b_ptr", b_ptr)] {
+        let other = span(ptr);
+        if other.start < c_span.end && c_span.start < other.end {
+            return Err(WasmError::new(
+                ErrorCode::InvalidArgument,
+                format!("{what}: c_ptr overlaps {name}"),
+            ));
         }
-        ii += bs;
     }
+    Ok(size)
 }
 
 /// Naive matrix multiplication - O(n^3)
+///
+/// # Errors
+///
+/// `SizeOverflow` if `n * n` overflows, and `BufferTooSmall` if `a`, `b` or `c` holds
+/// fewer than `n * n` values.
 #[wasm_bindgen]
-pub fn matrix_multiply(a: &[f64], b: &[f64], c: &mut [f64], n: usize) {
+pub fn matrix_multiply(a: &[f64], b: &[f64], c: &mut [f64], n: usize) -> Result<(), JsError> {
+    check_matrix_operands(a, b, c, n, "matrix_multiply")?;
+    let _ = matrix_multiply_impl(a, b, c, n, None);
+    Ok(())
+}
+
+/// Progress is reported in rows of C; `total` is `n`.
+fn matrix_multiply_impl(
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+    n: usize,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
     if n == 0 {
-        return;
+        return Ok(());
+    }
+    if let Some(control) = control {
+        control.begin(n);
     }
     if n <= BLOCK_THRESHOLD {
-        matrix_multiply_naive(a, b, c, n);
+        matrix_multiply_naive(a, b, c, n, control)
     } else {
-        matrix_multiply_blocked(a, b, c, n, BLOCK_SIZE);
+        matrix_multiply_blocked(a, b, c, n, BLOCK_SIZE, control)
     }
 }
 
 /// Strassen matrix multiplication - O(n^2.807)
 /// More efficient for large matrices (power of two, threshold tuned)
 /// Uses divide-and-conquer with 7 multiplications instead of 8
+///
+/// # Errors
+///
+/// As `matrix_multiply`.
 #[wasm_bindgen]
-pub fn matrix_multiply_strassen(a: &[f64], b: &[f64], c: &mut [f64], n: usize) {
+pub fn matrix_multiply_strassen(
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+    n: usize,
+) -> Result<(), JsError> {
+    check_matrix_operands(a, b, c, n, "matrix_multiply_strassen")?;
+    let _ = matrix_multiply_strassen_impl(a, b, c, n, None);
+    Ok(())
+}
+
+/// Progress is reported in leaf multiplications when the Strassen path runs
+/// (`total` is `7^levels`), otherwise in rows as in `matrix_multiply_impl`.
+fn matrix_multiply_strassen_impl(
+    a: &[f64],
+    b: &[f64],
+    c: &mut [f64],
+    n: usize,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
     if n == 0 {
-        return;
+        return Ok(());
     }
     let threshold = strassen_threshold();
 
     // For small matrices, use naive algorithm (threshold tuned for WASM)
     if n <= threshold {
-        matrix_multiply(a, b, c, n);
-        return;
+        return matrix_multiply_impl(a, b, c, n, control);
     }
 
     // Ensure n is power of 2 for Strassen
     if !is_power_of_two(n) {
         // Pad matrices and use naive for non-power-of-2
-        matrix_multiply(a, b, c, n);
-        return;
+        return matrix_multiply_impl(a, b, c, n, control);
     }
 
+    if let Some(control) = control {
+        control.begin(strassen_leaf_count(n, threshold));
+    }
     let workspace_len = workspace_required(n, threshold);
     let mut workspace = Workspace::with_capacity(workspace_len);
-    if strassen_recursive_ws(a, b, c, n, &mut workspace, threshold).is_err() {
+    match strassen_recursive_ws(a, b, c, n, &mut workspace, threshold, control) {
+        Ok(()) => Ok(()),
+        Err(StrassenError::Cancelled) => Err(Cancelled),
         // Fall back to robust implementation if workspace sizing assumptions are violated.
-        matrix_multiply(a, b, c, n);
+        Err(StrassenError::WorkspaceExhausted) => matrix_multiply_impl(a, b, c, n, control),
+    }
+}
+
+fn strassen_leaf_count(n: usize, threshold: usize) -> usize {
+    let mut size = n;
+    let mut leaves = 1usize;
+    while size > threshold {
+        size /= 2;
+        leaves = leaves.saturating_mul(7);
+    }
+    leaves
+}
+
+enum StrassenError {
+    WorkspaceExhausted,
+    Cancelled,
+}
+
+impl From<Cancelled> for StrassenError {
+    fn from(_: Cancelled) -> Self {
+        Self::Cancelled
     }
 }
 
@@ -606,43 +7366,45 @@ fn strassen_recursive_ws(
     n: usize,
     workspace: &mut Workspace,
     threshold: usize,
-) -> Result<(), &'static str> {
+    control: Option<&KernelControl>,
+) -> Result<(), StrassenError> {
     if n <= threshold {
         // Base case: use naive multiplication (avoid extra blocking overhead)
-        matrix_multiply_naive(a, b, c, n);
+        matrix_multiply_naive(a, b, c, n, None)?;
+        checkpoint(control, 1)?;
         return Ok(());
     }
 
     let mark = workspace.mark();
-    let result = (|| -> Result<(), &'static str> {
+    let result = (|| -> Result<(), StrassenError> {
         let half = n / 2;
         let size = half * half;
 
         // Allocate submatrices.
         let a11_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let a12_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let a21_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let a22_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let b11_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let b12_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let b21_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let b22_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
 
         // SAFETY: All pointers come from `workspace.alloc_ptr(size)` in this frame,
         // are unique non-overlapping regions, and `size` matches allocation length.
@@ -680,32 +7442,32 @@ fn strassen_recursive_ws(
         // Strassen's 7 products.
         let m1_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m2_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m3_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m4_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m5_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m6_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let m7_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
 
         let temp1_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
         let temp2_ptr = workspace
             .alloc_ptr(size)
-            .ok_or("workspace capacity exceeded")?;
+            .ok_or(StrassenError::WorkspaceExhausted)?;
 
         // SAFETY: All regions were allocated from workspace with identical `size` and
         // are uniquely owned by this recursion frame.
@@ -731,33 +7493,33 @@ fn strassen_recursive_ws(
         // M1 = (A11 + A22) * (B11 + B22)
         matrix_add(a11, a22, temp1, half);
         matrix_add(b11, b22, temp2, half);
-        strassen_recursive_ws(temp1, temp2, m1, half, workspace, threshold)?;
+        strassen_recursive_ws(temp1, temp2, m1, half, workspace, threshold, control)?;
 
         // M2 = (A21 + A22) * B11
         matrix_add(a21, a22, temp1, half);
-        strassen_recursive_ws(temp1, b11, m2, half, workspace, threshold)?;
+        strassen_recursive_ws(temp1, b11, m2, half, workspace, threshold, control)?;
 
         // M3 = A11 * (B12 - B22)
         matrix_sub(b12, b22, temp1, half);
-        strassen_recursive_ws(a11, temp1, m3, half, workspace, threshold)?;
+        strassen_recursive_ws(a11, temp1, m3, half, workspace, threshold, control)?;
 
         // M4 = A22 * (B21 - B11)
         matrix_sub(b21, b11, temp1, half);
-        strassen_recursive_ws(a22, temp1, m4, half, workspace, threshold)?;
+        strassen_recursive_ws(a22, temp1, m4, half, workspace, threshold, control)?;
 
         // M5 = (A11 + A12) * B22
         matrix_add(a11, a12, temp1, half);
-        strassen_recursive_ws(temp1, b22, m5, half, workspace, threshold)?;
+        strassen_recursive_ws(temp1, b22, m5, half, workspace, threshold, control)?;
 
         // M6 = (A21 - A11) * (B11 + B12)
         matrix_sub(a21, a11, temp1, half);
         matrix_add(b11, b12, temp2, half);
-        strassen_recursive_ws(temp1, temp2, m6, half, workspace, threshold)?;
+        strassen_recursive_ws(temp1, temp2, m6, half, workspace, threshold, control)?;
 
         // M7 = (A12 - A22) * (B21 + B22)
         matrix_sub(a12, a22, temp1, half);
         matrix_add(b21, b22, temp2, half);
-        strassen_recursive_ws(temp1, temp2, m7, half, workspace, threshold)?;
+        strassen_recursive_ws(temp1, temp2, m7, half, workspace, threshold, control)?;
 
         // Combine results:
         // C11 = M1 + M4 - M5 + M7
@@ -793,13 +7555,26 @@ fn matrix_sub(a: &[f64], b: &[f64], c: &mut [f64], n: usize) {
     }
 }
 
+/// Pointer variant of `matrix_multiply` for `n x n` row-major matrices already in wasm
+/// memory.
+///
+/// # Errors
+///
+/// `SizeOverflow` if `n * n` overflows, `NullPointer` if a pointer is null while
+/// `n > 0`, and `InvalidArgument` if a pointer is misaligned or `c_ptr` overlaps an
+/// input.
 #[wasm_bindgen]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn matrix_multiply_ptr(a_ptr: *const f64, b_ptr: *const f64, c_ptr: *mut f64, n: usize) {
-    if n == 0 || a_ptr.is_null() || b_ptr.is_null() || c_ptr.is_null() {
-        return;
+pub fn matrix_multiply_ptr(
+    a_ptr: *const f64,
+    b_ptr: *const f64,
+    c_ptr: *mut f64,
+    n: usize,
+) -> Result<(), JsError> {
+    let size = check_matrix_ptrs(a_ptr, b_ptr, c_ptr, n, "matrix_multiply_ptr")?;
+    if n == 0 {
+        return Ok(());
     }
-    let size = n * n;
     // SAFETY: Caller guarantees `a_ptr`/`b_ptr` point to `size` readable f64 values
     // and `c_ptr` points to `size` writable f64 values for this call duration.
     let a = unsafe { std::slice::from_raw_parts(a_ptr, size) };
@@ -807,9 +7582,15 @@ pub fn matrix_multiply_ptr(a_ptr: *const f64, b_ptr: *const f64, c_ptr: *mut f64
     let b = unsafe { std::slice::from_raw_parts(b_ptr, size) };
     // SAFETY: Same preconditions as above.
     let c = unsafe { std::slice::from_raw_parts_mut(c_ptr, size) };
-    matrix_multiply(a, b, c, n);
+    let _ = matrix_multiply_impl(a, b, c, n, None);
+    Ok(())
 }
 
+/// Pointer variant of `matrix_multiply_strassen`.
+///
+/// # Errors
+///
+/// As `matrix_multiply_ptr`.
 #[wasm_bindgen]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn matrix_multiply_strassen_ptr(
@@ -817,11 +7598,50 @@ pub fn matrix_multiply_strassen_ptr(
     b_ptr: *const f64,
     c_ptr: *mut f64,
     n: usize,
-) {
-    if n == 0 || a_ptr.is_null() || b_ptr.is_null() || c_ptr.is_null() {
-        return;
+) -> Result<(), JsError> {
+    let size = check_matrix_ptrs(a_ptr, b_ptr, c_ptr, n, "matrix_multiply_strassen_ptr")?;
+    if n == 0 {
+        return Ok(());
+    }
+    // SAFETY: Caller guarantees `a_ptr`/`b_ptr` point to `size` readable f64 values
+    // and `c_ptr` points to `size` writable f64 values for this call duration.
+    let a = unsafe { std::slice::from_raw_parts(a_ptr, size) };
+    // SAFETY: Same preconditions as above.
+    let b = unsafe { std::slice::from_raw_parts(b_ptr, size) };
+    // SAFETY: Same preconditions as above.
+    let c = unsafe { std::slice::from_raw_parts_mut(c_ptr, size) };
+    let _ = matrix_multiply_strassen_impl(a, b, c, n, None);
+    Ok(())
+}
+
+/// Same as `matrix_multiply_strassen_ptr`, reporting progress to the control block
+/// at `control_ptr` (see `alloc_control`) and stopping when cancelled.
+/// A cancelled call leaves `c` partially written.
+///
+/// # Errors
+///
+/// As `matrix_multiply_ptr`, plus `InvalidArgument` if `control_ptr` is not 4-byte
+/// aligned.
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn matrix_multiply_strassen_cancellable_ptr(
+    a_ptr: *const f64,
+    b_ptr: *const f64,
+    c_ptr: *mut f64,
+    n: usize,
+    control_ptr: *const i32,
+) -> Result<KernelStatus, JsError> {
+    let size = check_matrix_ptrs(
+        a_ptr,
+        b_ptr,
+        c_ptr,
+        n,
+        "matrix_multiply_strassen_cancellable_ptr",
+    )?;
+    let control = control_from_ptr(control_ptr)?;
+    if n == 0 {
+        return Ok(KernelStatus::Completed);
     }
-    let size = n * n;
     // SAFETY: Caller guarantees `a_ptr`/`b_ptr` point to `size` readable f64 values
     // and `c_ptr` points to `size` writable f64 values for this call duration.
     let a = unsafe { std::slice::from_raw_parts(a_ptr, size) };
@@ -829,7 +7649,9 @@ pub fn matrix_multiply_strassen_ptr(
     let b = unsafe { std::slice::from_raw_parts(b_ptr, size) };
     // SAFETY: Same preconditions as above.
     let c = unsafe { std::slice::from_raw_parts_mut(c_ptr, size) };
-    matrix_multiply_strassen(a, b, c, n);
+    Ok(kernel_status(matrix_multiply_strassen_impl(
+        a, b, c, n, control,
+    )))
 }
 
 // ============================================================================
@@ -838,44 +7660,139 @@ pub fn matrix_multiply_strassen_ptr(
 
 #[wasm_bindgen]
 pub fn quicksort(arr: &mut [f64]) {
+    let _ = quicksort_impl(arr, None);
+}
+
+/// Progress is reported in elements that reached their final position; `total` is the length.
+fn quicksort_impl(arr: &mut [f64], control: Option<&KernelControl>) -> Result<(), Cancelled> {
+    if let Some(control) = control {
+        control.begin(arr.len());
+    }
     if arr.len() <= 1 {
-        return;
+        return checkpoint(control, arr.len());
+    }
+    #[cfg(feature = "parallel")]
+    if arr.len() >= PARALLEL_MIN_LEN {
+        // Bound the fork depth so adversarial inputs cannot blow the stack;
+        // past the limit each half falls back to the iterative sort.
+        let depth = 2 * rayon::current_num_threads()
+            .next_power_of_two()
+            .trailing_zeros()
+            + 4;
+        return quicksort_par(arr, depth, control);
+    }
+    quicksort_iter(arr, control)
+}
+
+#[cfg(feature = "parallel")]
+fn quicksort_par(
+    arr: &mut [f64],
+    depth: u32,
+    control: Option<&KernelControl>,
+) -> Result<(), Cancelled> {
+    if arr.len() <= 1 {
+        return checkpoint(control, arr.len());
+    }
+    if depth == 0 || arr.len() < PARALLEL_MIN_LEN {
+        return quicksort_iter(arr, control);
+    }
+    let high = arr.len() - 1;
+    // Median-of-three keeps splits balanced on already sorted input.
+    let mid = high / 2;
+    if arr[mid] < arr[0] {
+        arr.swap(mid, 0);
+    }
+    if arr[high] < arr[0] {
+        arr.swap(high, 0);
     }
-    quicksort_iter(arr);
+    if arr[mid] < arr[high] {
+        arr.swap(mid, high);
+    }
+    let pivot = partition(arr, 0, high);
+    checkpoint(control, 1)?;
+    let (left, right) = arr.split_at_mut(pivot);
+    let (left_result, right_result) = rayon::join(
+        || quicksort_par(left, depth - 1, control),
+        || quicksort_par(&mut right[1..], depth - 1, control),
+    );
+    left_result.and(right_result)
 }
 
+/// Sorts `len` f64 values at `ptr` in wasm memory in place, ascending.
+///
+/// # Errors
+///
+/// `NullPointer` if `ptr` is null and `len` is non-zero.
 #[wasm_bindgen]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn quicksort_ptr(ptr: *mut f64, len: usize) {
-    if ptr.is_null() || len == 0 {
-        return;
+pub fn quicksort_ptr(ptr: *mut f64, len: usize) -> Result<(), JsError> {
+    require_non_null(ptr, len, "quicksort_ptr: ptr")?;
+    if len == 0 {
+        return Ok(());
     }
     // SAFETY: Caller guarantees `ptr` points to `len` writable f64 elements.
     let arr = unsafe { std::slice::from_raw_parts_mut(ptr, len) };
     quicksort(arr);
+    Ok(())
 }
 
+/// Same as `quicksort_ptr`, reporting sorted elements to the control block at
+/// `control_ptr` (see `alloc_control`) and stopping when cancelled.
+/// A cancelled sort leaves the buffer permuted but not sorted.
+///
+/// # Errors
+///
+/// As `quicksort_ptr`, plus `InvalidArgument` if `control_ptr` is not 4-byte aligned.
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn quicksort_cancellable_ptr(
+    ptr: *mut f64,
+    len: usize,
+    control_ptr: *const i32,
+) -> Result<KernelStatus, JsError> {
+    require_non_null(ptr, len, "quicksort_cancellable_ptr: ptr")?;
+    let control = control_from_ptr(control_ptr)?;
+    if len == 0 {
+        return Ok(KernelStatus::Completed);
+    }
+    // SAFETY: Caller guarantees `ptr` points to `len` writable f64 elements.
+    let arr = unsafe { std::slice::from_raw_parts_mut(ptr, len) };
+    Ok(kernel_status(quicksort_impl(arr, control)))
+}
+
+/// Partitions of at least this many elements, or this many finished elements,
+/// trigger a progress flush and cancellation check.
+const QS_PROGRESS_BATCH: usize = 1 << 14;
+
 thread_local! {
     static QS_STACK: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
 }
 
-fn quicksort_iter(arr: &mut [f64]) {
+fn quicksort_iter(arr: &mut [f64], control: Option<&KernelControl>) -> Result<(), Cancelled> {
     QS_STACK.with(|stack_cell| {
         let mut stack = stack_cell.borrow_mut();
         stack.clear();
         stack.push((0, arr.len() - 1));
+        let mut pending = 0usize;
 
         while let Some((low, high)) = stack.pop() {
             if low >= high {
+                pending += usize::from(low == high);
                 continue;
             }
             let pivot = partition(arr, low, high);
+            pending += 1;
+            if pending >= QS_PROGRESS_BATCH || high - low >= QS_PROGRESS_BATCH {
+                checkpoint(control, pending)?;
+                pending = 0;
+            }
             if pivot > 0 {
                 stack.push((low, pivot - 1));
             }
             stack.push((pivot + 1, high));
         }
-    });
+        checkpoint(control, pending)
+    })
 }
 
 fn partition(arr: &mut [f64], low: usize, high: usize) -> usize {
@@ -890,3 +7807,256 @@ fn partition(arr: &mut [f64], low: usize, high: usize) -> usize {
     arr.swap(i, high);
     i
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+/// Native checks. Inputs are sized past `PARALLEL_MIN_LEN`, so
+/// `cargo test --features parallel` exercises the rayon paths against serial
+/// references; without the feature the same tests cover the serial code. The
+/// cancellable kernels are driven through `alloc_control` pointers, as from JS.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LARGE: usize = 3 * (1 << 16) + 5;
+
+    /// Deterministic small integers, so float sums are exact in any order.
+    fn pattern(len: usize, modulus: u32) -> Vec<u32> {
+        (0..len)
+            .map(|i| u32::try_from(i).unwrap().wrapping_mul(2_654_435_761) % modulus)
+            .collect()
+    }
+
+    fn to_f32(values: &[u32]) -> Vec<f32> {
+        values
+            .iter()
+            .map(|&v| f32::from(u16::try_from(v).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn sums_match_serial() {
+        let values = pattern(LARGE, 1000);
+        let expected: u32 = values.iter().sum();
+        assert_eq!(sum_u32(&values), expected);
+        assert_eq!(sum_u32_sab(&values), expected);
+
+        let floats = to_f32(&pattern(LARGE, 7));
+        assert_eq!(
+            sum_f32_simd(&floats).to_bits(),
+            sum_f32_serial(&floats).to_bits()
+        );
+    }
+
+    #[test]
+    fn dot_product_matches_serial() {
+        let a = to_f32(&pattern(LARGE, 5));
+        let b = to_f32(&pattern(LARGE + 11, 3)[11..]);
+        let parallel = dot_product_simd(&a, &b).unwrap();
+        assert_eq!(parallel.to_bits(), dot_product_serial(&a, &b).to_bits());
+    }
+
+    #[test]
+    fn box_blur_matches_direct_average() {
+        let (w, h) = (181, 97);
+        let source: Vec<u8> = pattern(w * h * 4, 256)
+            .into_iter()
+            .map(|v| u8::try_from(v).unwrap())
+            .collect();
+        // Summed-area table per channel: the clipped box sum in O(1).
+        let mut table = vec![[0usize; 3]; (w + 1) * (h + 1)];
+        for y in 0..h {
+            for x in 0..w {
+                for channel in 0..3 {
+                    table[(y + 1) * (w + 1) + x + 1][channel] =
+                        usize::from(source[(y * w + x) * 4 + channel])
+                            + table[y * (w + 1) + x + 1][channel]
+                            + table[(y + 1) * (w + 1) + x][channel]
+                            - table[y * (w + 1) + x][channel];
+                }
+            }
+        }
+        for radius in [0, 1, 6, 200] {
+            let mut blurred = source.clone();
+            box_blur(&mut blurred, 181, 97, radius).unwrap();
+            let r = usize::try_from(radius).unwrap();
+            for y in 0..h {
+                let (top, bottom) = (y.saturating_sub(r), (y + r).min(h - 1) + 1);
+                for x in 0..w {
+                    let (left, right) = (x.saturating_sub(r), (x + r).min(w - 1) + 1);
+                    let count = (bottom - top) * (right - left);
+                    for channel in 0..3 {
+                        let corner = |cy: usize, cx: usize| table[cy * (w + 1) + cx][channel];
+                        let total = corner(bottom, right) + corner(top, left)
+                            - corner(top, right)
+                            - corner(bottom, left);
+                        let got = usize::from(blurred[(y * w + x) * 4 + channel]);
+                        assert_eq!(got, total / count, "r={radius} ({x}, {y}) ch {channel}");
+                    }
+                    assert_eq!(blurred[(y * w + x) * 4 + 3], source[(y * w + x) * 4 + 3]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blocked_matrix_multiply_matches_naive() {
+        let n = 260;
+        let a: Vec<f64> = pattern(n * n, 9).into_iter().map(f64::from).collect();
+        let b: Vec<f64> = pattern(n * n + 3, 11)[3..]
+            .iter()
+            .copied()
+            .map(f64::from)
+            .collect();
+        let mut blocked = vec![f64::NAN; n * n];
+        let mut naive = vec![0.0; n * n];
+        matrix_multiply_blocked(&a, &b, &mut blocked, n, BLOCK_SIZE, None).unwrap();
+        matrix_multiply_naive(&a, &b, &mut naive, n, None).unwrap();
+        assert_eq!(blocked, naive);
+    }
+
+    #[test]
+    fn split_radix_matches_radix2() {
+        for bits in 1..=12 {
+            let n = 1 << bits;
+            let input = to_f32(&pattern(2 * n, 1000));
+            let (mut re, mut im): (Vec<f64>, Vec<f64>) = input
+                .chunks(2)
+                .map(|pair| (f64::from(pair[0]), f64::from(pair[1])))
+                .unzip();
+            let (mut ref_re, mut ref_im) = (re.clone(), im.clone());
+            fft_pow2(&mut re, &mut im, FftAlgorithm::SplitRadix);
+            fft_pow2(&mut ref_re, &mut ref_im, FftAlgorithm::Radix2);
+            let scale = usize_to_f64(n) * 1000.0;
+            for k in 0..n {
+                let error = (re[k] - ref_re[k]).hypot(im[k] - ref_im[k]);
+                assert!(error <= 1e-12 * scale, "n={n} k={k} error={error}");
+            }
+        }
+    }
+
+    #[test]
+    fn plan_cache_evicts_least_recently_used_length() {
+        let mut cache = LenCache::new();
+        for n in 0..FFT_CACHE_CAPACITY {
+            cache.insert(n, Rc::new(n));
+        }
+        assert_eq!(cache.get(0).as_deref(), Some(&0));
+        cache.insert(FFT_CACHE_CAPACITY, Rc::new(FFT_CACHE_CAPACITY));
+        assert_eq!(cache.entries.len(), FFT_CACHE_CAPACITY);
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.get(0).as_deref(), Some(&0));
+
+        for n in 1..=64 {
+            kernel_for(n).unwrap();
+        }
+        PLAN_CACHE.with(|cache| assert_eq!(cache.borrow().entries.len(), FFT_CACHE_CAPACITY));
+        TWIDDLE_CACHE.with(|cache| assert_eq!(cache.borrow().entries.len(), FFT_CACHE_CAPACITY));
+    }
+
+    #[test]
+    fn control_block_round_trips_through_raw_pointer() {
+        let ptr = alloc_control();
+        // SAFETY: `alloc_control` returns three live, zeroed `i32` words.
+        assert_eq!(unsafe { std::slice::from_raw_parts(ptr, 3) }, [0, 0, 0]);
+        let control = control_from_ptr(ptr).unwrap().unwrap();
+        control.cancel();
+        // SAFETY: As above; the block is still live.
+        assert_eq!(unsafe { *ptr }, 1);
+        free_control(ptr);
+        free_control(std::ptr::null_mut());
+        assert!(control_from_ptr(std::ptr::null()).unwrap().is_none());
+    }
+
+    #[test]
+    fn completed_kernels_report_full_progress() {
+        let ptr = alloc_control();
+        let control = control_from_ptr(ptr).unwrap().unwrap();
+
+        let mut data: Vec<f64> = pattern(LARGE, 5000).into_iter().map(f64::from).collect();
+        let status = quicksort_cancellable_ptr(data.as_mut_ptr(), data.len(), ptr).unwrap();
+        assert_eq!(status, KernelStatus::Completed);
+        assert_eq!(control.total(), i32::try_from(LARGE).unwrap());
+        assert_eq!(control.progress(), control.total());
+
+        let n = 300;
+        let a: Vec<f64> = pattern(n * n, 7).into_iter().map(f64::from).collect();
+        let mut c = vec![0.0; n * n];
+        let status = matrix_multiply_strassen_cancellable_ptr(
+            a.as_ptr(),
+            a.as_ptr(),
+            c.as_mut_ptr(),
+            n,
+            ptr,
+        )
+        .unwrap();
+        assert_eq!(status, KernelStatus::Completed);
+        assert!(control.total() > 0);
+        assert_eq!(control.progress(), control.total());
+
+        let input: Vec<f64> = (0..1000).map(usize_to_f64).collect();
+        let mut spectrum = vec![0.0; input.len()];
+        let status = fft_demo_cancellable(&input, &mut spectrum, ptr).unwrap();
+        assert_eq!(status, KernelStatus::Completed);
+        assert_eq!(control.total(), 1000);
+        assert_eq!(control.progress(), 1000);
+
+        free_control(ptr);
+    }
+
+    #[test]
+    fn cancelled_kernels_stop_early() {
+        let ptr = alloc_control();
+        let control = control_from_ptr(ptr).unwrap().unwrap();
+        control.cancel();
+
+        let input: Vec<f64> = pattern(LARGE, 5000).into_iter().map(f64::from).collect();
+        let mut data = input.clone();
+        let status = quicksort_cancellable_ptr(data.as_mut_ptr(), data.len(), ptr).unwrap();
+        assert_eq!(status, KernelStatus::Cancelled);
+        assert!(control.progress() < control.total());
+        // A cancelled sort only permutes the buffer.
+        let (mut expected, mut got) = (input, data);
+        expected.sort_by(f64::total_cmp);
+        got.sort_by(f64::total_cmp);
+        assert_eq!(got, expected);
+
+        let n = 300;
+        let a = vec![1.0; n * n];
+        let mut c = vec![0.0; n * n];
+        let status = matrix_multiply_strassen_cancellable_ptr(
+            a.as_ptr(),
+            a.as_ptr(),
+            c.as_mut_ptr(),
+            n,
+            ptr,
+        )
+        .unwrap();
+        assert_eq!(status, KernelStatus::Cancelled);
+        assert!(control.progress() < control.total());
+
+        let mut spectrum = vec![f64::NAN; 1000];
+        let status = fft_demo_cancellable(&[1.0; 1000], &mut spectrum, ptr).unwrap();
+        assert_eq!(status, KernelStatus::Cancelled);
+        assert_eq!(control.progress(), 0);
+        assert!(spectrum.iter().all(|v| v.is_nan()));
+
+        free_control(ptr);
+    }
+
+    #[test]
+    fn quicksort_matches_std_sort() {
+        // Last-element pivots make sorted runs quadratic in `quicksort_iter`,
+        // so stick to scrambled inputs, with and without repeated keys.
+        for modulus in [u32::MAX, 5000] {
+            let input: Vec<f64> = pattern(LARGE, modulus).into_iter().map(f64::from).collect();
+            let mut expected = input.clone();
+            expected.sort_by(f64::total_cmp);
+            let mut sorted = input;
+            quicksort(&mut sorted);
+            assert_eq!(sorted, expected);
+        }
+    }
+}
@@ -2,7 +2,10 @@ import { spawnSync } from 'node:child_process';
 import { existsSync } from 'node:fs';
 import { join } from 'node:path';
 
-const isShared = process.env.WASM_SHARED === '1' || process.env.WASM_SHARED === 'true';
+const isParallel = process.env.WASM_PARALLEL === '1' || process.env.WASM_PARALLEL === 'true';
+// The rayon thread pool lives on shared wasm memory, so parallel implies shared.
+const isShared =
+  isParallel || process.env.WASM_SHARED === '1' || process.env.WASM_SHARED === 'true';
 const sharedRustFlags = [
   '-C',
   'target-feature=+atomics,+bulk-memory,+mutable-globals',
@@ -62,6 +65,10 @@ if (isShared) {
   cargoArgs.push('-Z', 'build-std=std,panic_abort');
 }
 
+if (isParallel) {
+  cargoArgs.push('--features', 'parallel');
+}
+
 run('cargo', cargoArgs, rustflags ? { RUSTFLAGS: rustflags } : {});
 
 const wasmPath = join(
@@ -13,6 +13,176 @@ const ensureStub = () => {
   throw new Error('WASM module not built. Run: npm run build:wasm');
 };
 
+export const ErrorCode = Object.freeze({
+  NullPointer: 1,
+  1: 'NullPointer',
+  BufferTooSmall: 2,
+  2: 'BufferTooSmall',
+  LengthMismatch: 3,
+  3: 'LengthMismatch',
+  InvalidDimensions: 4,
+  4: 'InvalidDimensions',
+  SizeOverflow: 5,
+  5: 'SizeOverflow',
+  InvalidArgument: 6,
+  6: 'InvalidArgument',
+  InvalidFormat: 7,
+  7: 'InvalidFormat',
+});
+
+export const KernelStatus = Object.freeze({
+  Completed: 0,
+  0: 'Completed',
+  Cancelled: 1,
+  1: 'Cancelled',
+});
+
+export const FftAlgorithm = Object.freeze({
+  Radix2: 0,
+  0: 'Radix2',
+  SplitRadix: 1,
+  1: 'SplitRadix',
+});
+
+export const SpectrumKind = Object.freeze({
+  Magnitude: 0,
+  0: 'Magnitude',
+  Phase: 1,
+  1: 'Phase',
+  Power: 2,
+  2: 'Power',
+});
+
+export const WindowKind = Object.freeze({
+  Rectangular: 0,
+  0: 'Rectangular',
+  Hann: 1,
+  1: 'Hann',
+  Hamming: 2,
+  2: 'Hamming',
+  Blackman: 3,
+  3: 'Blackman',
+  BlackmanHarris: 4,
+  4: 'BlackmanHarris',
+  Kaiser: 5,
+  5: 'Kaiser',
+  FlatTop: 6,
+  6: 'FlatTop',
+  Tukey: 7,
+  7: 'Tukey',
+});
+
+export const SpectrogramScale = Object.freeze({
+  Linear: 0,
+  0: 'Linear',
+  Decibels: 1,
+  1: 'Decibels',
+});
+
+export const Waveform = Object.freeze({
+  Sine: 0,
+  0: 'Sine',
+  Square: 1,
+  1: 'Square',
+  Sawtooth: 2,
+  2: 'Sawtooth',
+  Triangle: 3,
+  3: 'Triangle',
+});
+
+export const NoiseColor = Object.freeze({
+  White: 0,
+  0: 'White',
+  Pink: 1,
+  1: 'Pink',
+  Brown: 2,
+  2: 'Brown',
+});
+
+export const ChirpKind = Object.freeze({
+  Linear: 0,
+  0: 'Linear',
+  Logarithmic: 1,
+  1: 'Logarithmic',
+});
+
+export const FilterBand = Object.freeze({
+  Lowpass: 0,
+  0: 'Lowpass',
+  Highpass: 1,
+  1: 'Highpass',
+  Bandpass: 2,
+  2: 'Bandpass',
+  Bandstop: 3,
+  3: 'Bandstop',
+});
+
+export const BiquadKind = Object.freeze({
+  Lowpass: 0,
+  0: 'Lowpass',
+  Highpass: 1,
+  1: 'Highpass',
+  Bandpass: 2,
+  2: 'Bandpass',
+  Notch: 3,
+  3: 'Notch',
+  Peaking: 4,
+  4: 'Peaking',
+  LowShelf: 5,
+  5: 'LowShelf',
+  HighShelf: 6,
+  6: 'HighShelf',
+});
+
+export const IirPrototype = Object.freeze({
+  Butterworth: 0,
+  0: 'Butterworth',
+  Chebyshev1: 1,
+  1: 'Chebyshev1',
+});
+
+export const PitchMethod = Object.freeze({
+  Yin: 0,
+  0: 'Yin',
+  Autocorrelation: 1,
+  1: 'Autocorrelation',
+});
+
+export const WavFormat = Object.freeze({
+  Pcm8: 0,
+  0: 'Pcm8',
+  Pcm16: 1,
+  1: 'Pcm16',
+  Pcm24: 2,
+  2: 'Pcm24',
+  Pcm32: 3,
+  3: 'Pcm32',
+  Float32: 4,
+  4: 'Float32',
+  Float64: 5,
+  5: 'Float64',
+});
+
+export const EdgeMode = Object.freeze({
+  Clamp: 0,
+  0: 'Clamp',
+  Mirror: 1,
+  1: 'Mirror',
+  Wrap: 2,
+  2: 'Wrap',
+  Constant: 3,
+  3: 'Constant',
+});
+
+export const GradientOperator = Object.freeze({
+  Sobel: 0,
+  0: 'Sobel',
+  Scharr: 1,
+  1: 'Scharr',
+  Prewitt: 2,
+  2: 'Prewitt',
+});
+
 export default async function init() {
   return {
     memory: { buffer: new ArrayBuffer(0) },
@@ -20,10 +190,8 @@ export default async function init() {
     free_f64: stubError,
     alloc_u32: stubError,
     free_u32: stubError,
-    process_shared_buffer_ptr: stubError,
-    matrix_multiply_ptr: stubError,
-    matrix_multiply_strassen_ptr: stubError,
-    quicksort_ptr: stubError,
+    alloc_control: stubError,
+    free_control: stubError,
   };
 }
 
@@ -67,14 +235,322 @@ export function box_blur() {
   stubError();
 }
 
+export function gaussian_blur() {
+  stubError();
+}
+
+export function convolve_rgba() {
+  stubError();
+}
+
+export function image_gradient() {
+  stubError();
+}
+
+export function gradient_magnitude() {
+  stubError();
+}
+
+export function canny() {
+  stubError();
+}
+
 export function fft_demo() {
   stubError();
 }
 
+export function fft_demo_cancellable() {
+  stubError();
+}
+
+export function fft_complex() {
+  stubError();
+}
+
+export function fft_complex_f32() {
+  stubError();
+}
+
+export function fft_interleaved() {
+  stubError();
+}
+
+export function fft_interleaved_f32() {
+  stubError();
+}
+
+export function fft_spectrum() {
+  stubError();
+}
+
+export function fft_spectrum_interleaved() {
+  stubError();
+}
+
+export function ifft_complex() {
+  stubError();
+}
+
+export function ifft_interleaved() {
+  stubError();
+}
+
+export function rfft() {
+  stubError();
+}
+
+export function rfft_interleaved() {
+  stubError();
+}
+
+export function irfft() {
+  stubError();
+}
+
+export function irfft_interleaved() {
+  stubError();
+}
+
+export class FftPlan {
+  constructor() {
+    stubError();
+  }
+}
+
 export function generate_signal() {
   stubError();
 }
 
+export function goertzel() {
+  stubError();
+}
+
+export function generate_dtmf() {
+  stubError();
+}
+
+export function decode_dtmf() {
+  stubError();
+}
+
+export class SignalGenerator {
+  constructor() {
+    stubError();
+  }
+}
+
+export function generate_window() {
+  stubError();
+}
+
+export function apply_window() {
+  stubError();
+}
+
+export function apply_window_f32() {
+  stubError();
+}
+
+export function window_coherent_gain() {
+  stubError();
+}
+
+export function window_enbw() {
+  stubError();
+}
+
+export function stft_frame_count() {
+  stubError();
+}
+
+export function stft() {
+  stubError();
+}
+
+export function stft_magnitude() {
+  stubError();
+}
+
+export function istft() {
+  stubError();
+}
+
+export function mel_filterbank() {
+  stubError();
+}
+
+export function dct2() {
+  stubError();
+}
+
+export class MelExtractor {
+  constructor() {
+    stubError();
+  }
+}
+
+export function welch_psd() {
+  stubError();
+}
+
+export function rms() {
+  stubError();
+}
+
+export function peak_amplitude() {
+  stubError();
+}
+
+export function crest_factor() {
+  stubError();
+}
+
+export class HarmonicAnalysis {
+  constructor() {
+    stubError();
+  }
+}
+
+export function analyze_harmonics() {
+  stubError();
+}
+
+export function analytic_signal() {
+  stubError();
+}
+
+export function hilbert_envelope() {
+  stubError();
+}
+
+export function instantaneous_frequency() {
+  stubError();
+}
+
+export function fft2d() {
+  stubError();
+}
+
+export function ifft2d() {
+  stubError();
+}
+
+export function fftshift2d() {
+  stubError();
+}
+
+export function ifftshift2d() {
+  stubError();
+}
+
+export function image_spectrum() {
+  stubError();
+}
+
+export function frequency_filter() {
+  stubError();
+}
+
+export function fir_design_windowed() {
+  stubError();
+}
+
+export function fir_design_equiripple() {
+  stubError();
+}
+
+export function fir_magnitude_response() {
+  stubError();
+}
+
+export function fir_filter() {
+  stubError();
+}
+
+export class FirFilter {
+  constructor() {
+    stubError();
+  }
+}
+
+export class BiquadCascade {
+  constructor() {
+    stubError();
+  }
+
+  static design() {
+    stubError();
+  }
+}
+
+export function convolve() {
+  stubError();
+}
+
+export function convolve_f32() {
+  stubError();
+}
+
+export function correlate() {
+  stubError();
+}
+
+export function correlate_f32() {
+  stubError();
+}
+
+export function autocorrelate() {
+  stubError();
+}
+
+export function estimate_delay() {
+  stubError();
+}
+
+export function find_peaks() {
+  stubError();
+}
+
+export function estimate_pitch() {
+  stubError();
+}
+
+export class Resampler {
+  constructor() {
+    stubError();
+  }
+}
+
+export function resampled_len() {
+  stubError();
+}
+
+export function resample() {
+  stubError();
+}
+
+export function resample_f32() {
+  stubError();
+}
+
+export class WavAudio {
+  constructor() {
+    stubError();
+  }
+}
+
+export function decode_wav() {
+  stubError();
+}
+
+export function encode_wav() {
+  stubError();
+}
+
+export function encode_wav_f32() {
+  stubError();
+}
+
 export function matrix_multiply() {
   stubError();
 }
@@ -83,6 +559,18 @@ export function matrix_multiply_strassen() {
   stubError();
 }
 
+export function matrix_multiply_ptr() {
+  stubError();
+}
+
+export function matrix_multiply_strassen_ptr() {
+  stubError();
+}
+
+export function matrix_multiply_strassen_cancellable_ptr() {
+  stubError();
+}
+
 export function set_strassen_threshold() {
   stubError();
 }
@@ -94,6 +582,18 @@ export function get_strassen_threshold() {
 export function quicksort() {
   stubError();
 }
+
+export function quicksort_ptr() {
+  stubError();
+}
+
+export function quicksort_cancellable_ptr() {
+  stubError();
+}
+
+export function thread_count() {
+  return 1;
+}
 `;
 
   const dtsStub = `export default function init(): Promise<{
@@ -102,11 +602,102 @@ export function quicksort() {
   free_f64: (ptr: number, len: number) => void;
   alloc_u32: (len: number) => number;
   free_u32: (ptr: number, len: number) => void;
-  process_shared_buffer_ptr: (ptr: number, len: number) => void;
-  matrix_multiply_ptr: (aPtr: number, bPtr: number, cPtr: number, n: number) => void;
-  matrix_multiply_strassen_ptr: (aPtr: number, bPtr: number, cPtr: number, n: number) => void;
-  quicksort_ptr: (ptr: number, len: number) => void;
+  alloc_control: () => number;
+  free_control: (ptr: number) => void;
 }>;
+export enum ErrorCode {
+  NullPointer = 1,
+  BufferTooSmall = 2,
+  LengthMismatch = 3,
+  InvalidDimensions = 4,
+  SizeOverflow = 5,
+  InvalidArgument = 6,
+  InvalidFormat = 7,
+}
+export enum KernelStatus {
+  Completed = 0,
+  Cancelled = 1,
+}
+export enum FftAlgorithm {
+  Radix2 = 0,
+  SplitRadix = 1,
+}
+export enum SpectrumKind {
+  Magnitude = 0,
+  Phase = 1,
+  Power = 2,
+}
+export enum WindowKind {
+  Rectangular = 0,
+  Hann = 1,
+  Hamming = 2,
+  Blackman = 3,
+  BlackmanHarris = 4,
+  Kaiser = 5,
+  FlatTop = 6,
+  Tukey = 7,
+}
+export enum Waveform {
+  Sine = 0,
+  Square = 1,
+  Sawtooth = 2,
+  Triangle = 3,
+}
+export enum NoiseColor {
+  White = 0,
+  Pink = 1,
+  Brown = 2,
+}
+export enum ChirpKind {
+  Linear = 0,
+  Logarithmic = 1,
+}
+export enum FilterBand {
+  Lowpass = 0,
+  Highpass = 1,
+  Bandpass = 2,
+  Bandstop = 3,
+}
+export enum BiquadKind {
+  Lowpass = 0,
+  Highpass = 1,
+  Bandpass = 2,
+  Notch = 3,
+  Peaking = 4,
+  LowShelf = 5,
+  HighShelf = 6,
+}
+export enum IirPrototype {
+  Butterworth = 0,
+  Chebyshev1 = 1,
+}
+export enum PitchMethod {
+  Yin = 0,
+  Autocorrelation = 1,
+}
+export enum WavFormat {
+  Pcm8 = 0,
+  Pcm16 = 1,
+  Pcm24 = 2,
+  Pcm32 = 3,
+  Float32 = 4,
+  Float64 = 5,
+}
+export enum SpectrogramScale {
+  Linear = 0,
+  Decibels = 1,
+}
+export enum EdgeMode {
+  Clamp = 0,
+  Mirror = 1,
+  Wrap = 2,
+  Constant = 3,
+}
+export enum GradientOperator {
+  Sobel = 0,
+  Scharr = 1,
+  Prewitt = 2,
+}
 export function fibonacci(n: number): number;
 export function fibonacci_iter(n: number): bigint;
 export function process_shared_buffer(arr: Uint32Array): void;
@@ -117,13 +708,171 @@ export function sum_f32_simd(arr: Float32Array): number;
 export function dot_product_simd(a: Float32Array, b: Float32Array): number;
 export function grayscale(data: Uint8Array): void;
 export function box_blur(data: Uint8Array, width: number, height: number, radius: number): void;
+export function gaussian_blur(data: Uint8Array, width: number, height: number, sigma: number, edge: EdgeMode): void;
+export function convolve_rgba(data: Uint8Array, width: number, height: number, kernel: Float32Array, kernel_width: number, kernel_height: number, divisor: number, bias: number, edge: EdgeMode, include_alpha: boolean): void;
+export function image_gradient(data: Uint8Array, width: number, height: number, operator: GradientOperator, magnitude: Float32Array, direction: Float32Array): void;
+export function gradient_magnitude(data: Uint8Array, width: number, height: number, operator: GradientOperator, out: Uint8Array): void;
+export function canny(data: Uint8Array, width: number, height: number, sigma: number, low: number, high: number, operator: GradientOperator, out: Uint8Array): number;
 export function fft_demo(input: Float64Array, output: Float64Array): void;
+export function fft_demo_cancellable(input: Float64Array, output: Float64Array, control_ptr: number): KernelStatus;
+export function fft_complex(re: Float64Array, im: Float64Array, algorithm: FftAlgorithm): void;
+export function fft_complex_f32(re: Float32Array, im: Float32Array, algorithm: FftAlgorithm): void;
+export function fft_interleaved(data: Float64Array, algorithm: FftAlgorithm): void;
+export function fft_interleaved_f32(data: Float32Array, algorithm: FftAlgorithm): void;
+export function fft_spectrum(re: Float64Array, im: Float64Array, out: Float64Array, kind: SpectrumKind): void;
+export function fft_spectrum_interleaved(data: Float64Array, out: Float64Array, kind: SpectrumKind): void;
+export function ifft_complex(re: Float64Array, im: Float64Array, algorithm: FftAlgorithm): void;
+export function ifft_interleaved(data: Float64Array, algorithm: FftAlgorithm): void;
+export function rfft(input: Float64Array, out_re: Float64Array, out_im: Float64Array): void;
+export function rfft_interleaved(input: Float64Array, out: Float64Array): void;
+export function irfft(re: Float64Array, im: Float64Array, output: Float64Array): void;
+export function irfft_interleaved(data: Float64Array, output: Float64Array): void;
+export class FftPlan {
+  constructor(len: number);
+  free(): void;
+  readonly len: number;
+  is_empty(): boolean;
+  forward(re: Float64Array, im: Float64Array): void;
+  forward_interleaved(data: Float64Array): void;
+  inverse(re: Float64Array, im: Float64Array): void;
+  inverse_interleaved(data: Float64Array): void;
+}
 export function generate_signal(buffer: Float64Array, freq1: number, freq2: number, freq3: number): void;
+export function goertzel(signal: Float64Array, sample_rate: number, frequencies: Float64Array, power: Float64Array): void;
+export function generate_dtmf(buffer: Float64Array, sample_rate: number, digits: string, tone_seconds: number, gap_seconds: number): number;
+export function decode_dtmf(signal: Float64Array, sample_rate: number): string;
+export class SignalGenerator {
+  constructor(sample_rate: number);
+  free(): void;
+  readonly sample_rate: number;
+  dc_offset: number;
+  readonly component_count: number;
+  clear(): void;
+  add_sine(frequency: number, amplitude: number, phase: number): void;
+  add_wave(shape: Waveform, frequency: number, amplitude: number, phase: number): void;
+  add_pulse(frequency: number, amplitude: number, phase: number, duty: number): void;
+  add_noise(color: NoiseColor, amplitude: number, seed: number): void;
+  add_chirp(kind: ChirpKind, f0: number, f1: number, duration: number, amplitude: number, phase: number): void;
+  render(buffer: Float64Array): void;
+  render_f32(buffer: Float32Array): void;
+}
+export function generate_window(out: Float64Array, kind: WindowKind, param: number, periodic: boolean): void;
+export function apply_window(data: Float64Array, kind: WindowKind, param: number, periodic: boolean): void;
+export function apply_window_f32(data: Float32Array, kind: WindowKind, param: number, periodic: boolean): void;
+export function window_coherent_gain(kind: WindowKind, param: number, len: number, periodic: boolean): number;
+export function window_enbw(kind: WindowKind, param: number, len: number, periodic: boolean): number;
+export function stft_frame_count(signal_len: number, frame_size: number, hop: number): number;
+export function stft(signal: Float64Array, out_re: Float64Array, out_im: Float64Array, frame_size: number, hop: number, window: WindowKind, window_param: number): number;
+export function stft_magnitude(signal: Float64Array, out: Float64Array, frame_size: number, hop: number, window: WindowKind, window_param: number, scale: SpectrogramScale): number;
+export function istft(re: Float64Array, im: Float64Array, output: Float64Array, frame_size: number, hop: number, window: WindowKind, window_param: number): void;
+export function mel_filterbank(out: Float64Array, n_mels: number, frame_size: number, sample_rate: number, f_min: number, f_max: number): void;
+export function dct2(input: Float64Array, output: Float64Array): void;
+export class MelExtractor {
+  constructor(sample_rate: number, frame_size: number, hop: number, n_mels: number, f_min: number, f_max: number);
+  free(): void;
+  readonly frame_size: number;
+  readonly hop: number;
+  readonly n_mels: number;
+  frame_count(signal_len: number): number;
+  mel_spectrogram(signal: Float64Array, out: Float64Array): number;
+  log_mel_spectrogram(signal: Float64Array, out: Float64Array): number;
+  mfcc(signal: Float64Array, out: Float64Array, n_coeffs: number): number;
+}
+export function welch_psd(signal: Float64Array, out: Float64Array, sample_rate: number, segment_size: number, overlap: number, window: WindowKind, window_param: number): number;
+export function rms(signal: Float64Array): number;
+export function peak_amplitude(signal: Float64Array): number;
+export function crest_factor(signal: Float64Array): number;
+export class HarmonicAnalysis {
+  private constructor();
+  free(): void;
+  readonly fundamental: number;
+  readonly snr_db: number;
+  readonly thd_db: number;
+  readonly sinad_db: number;
+}
+export function analyze_harmonics(signal: Float64Array, sample_rate: number, fundamental: number, harmonics: number): HarmonicAnalysis;
+export function analytic_signal(signal: Float64Array, out_re: Float64Array, out_im: Float64Array): void;
+export function hilbert_envelope(signal: Float64Array, envelope: Float64Array, phase: Float64Array, unwrap_phase: boolean): void;
+export function instantaneous_frequency(signal: Float64Array, sample_rate: number, out: Float64Array): void;
+export function fft2d(re: Float64Array, im: Float64Array, width: number, height: number): void;
+export function ifft2d(re: Float64Array, im: Float64Array, width: number, height: number): void;
+export function fftshift2d(data: Float64Array, width: number, height: number): void;
+export function ifftshift2d(data: Float64Array, width: number, height: number): void;
+export function image_spectrum(data: Uint8Array, width: number, height: number, out: Uint8Array): void;
+export function frequency_filter(data: Uint8Array, width: number, height: number, band: FilterBand, cutoff: number): void;
+export function fir_design_windowed(taps: Float64Array, band: FilterBand, sample_rate: number, f1: number, f2: number, window: WindowKind, window_param: number): void;
+export function fir_design_equiripple(taps: Float64Array, band: FilterBand, sample_rate: number, f1: number, f2: number, transition_width: number, stopband_weight: number): void;
+export function fir_magnitude_response(taps: Float64Array, sample_rate: number, frequencies: Float64Array, out: Float64Array): void;
+export function fir_filter(input: Float64Array, taps: Float64Array, output: Float64Array): void;
+export class FirFilter {
+  constructor(taps: Float64Array);
+  free(): void;
+  readonly taps_len: number;
+  readonly group_delay: number;
+  reset(): void;
+  process(input: Float32Array, output: Float32Array): void;
+  process_in_place(data: Float32Array): void;
+}
+export class BiquadCascade {
+  constructor();
+  free(): void;
+  static design(prototype: IirPrototype, band: FilterBand, order: number, sample_rate: number, cutoff: number, ripple_db: number): BiquadCascade;
+  readonly section_count: number;
+  add_rbj(kind: BiquadKind, sample_rate: number, frequency: number, q: number, gain_db: number): void;
+  add_section(b0: number, b1: number, b2: number, a0: number, a1: number, a2: number): void;
+  reset(): void;
+  process(data: Float64Array): void;
+  process_f32(data: Float32Array): void;
+  frequency_response(sample_rate: number, frequencies: Float64Array, magnitude: Float64Array, phase: Float64Array): void;
+}
+export function convolve(a: Float64Array, b: Float64Array, out: Float64Array): void;
+export function convolve_f32(a: Float32Array, b: Float32Array, out: Float32Array): void;
+export function correlate(a: Float64Array, b: Float64Array, out: Float64Array): void;
+export function correlate_f32(a: Float32Array, b: Float32Array, out: Float32Array): void;
+export function autocorrelate(x: Float64Array, out: Float64Array): void;
+export function estimate_delay(reference: Float64Array, signal: Float64Array, max_lag: number): number;
+export function find_peaks(data: Float64Array, min_prominence: number, min_distance: number, positions: Float64Array, heights: Float64Array): number;
+export function estimate_pitch(signal: Float64Array, sample_rate: number, min_freq: number, max_freq: number, method: PitchMethod): number;
+export class Resampler {
+  constructor(input_rate: number, output_rate: number);
+  free(): void;
+  readonly input_rate: number;
+  readonly output_rate: number;
+  readonly taps_len: number;
+  output_len(input_len: number): number;
+  flush_len(): number;
+  reset(): void;
+  process(input: Float64Array, output: Float64Array): number;
+  process_f32(input: Float32Array, output: Float32Array): number;
+  flush(output: Float64Array): number;
+  flush_f32(output: Float32Array): number;
+}
+export function resampled_len(input_len: number, input_rate: number, output_rate: number): number;
+export function resample(input: Float64Array, input_rate: number, output_rate: number, output: Float64Array): number;
+export function resample_f32(input: Float32Array, input_rate: number, output_rate: number, output: Float32Array): number;
+export class WavAudio {
+  private constructor();
+  free(): void;
+  readonly sample_rate: number;
+  readonly format: WavFormat;
+  readonly channel_count: number;
+  readonly frames: number;
+  channel_data(index: number): Float32Array;
+}
+export function decode_wav(bytes: Uint8Array): WavAudio;
+export function encode_wav(planar: Float64Array, channels: number, sample_rate: number, format: WavFormat): Uint8Array;
+export function encode_wav_f32(planar: Float32Array, channels: number, sample_rate: number, format: WavFormat): Uint8Array;
 export function matrix_multiply(a: Float64Array, b: Float64Array, c: Float64Array, n: number): void;
 export function matrix_multiply_strassen(a: Float64Array, b: Float64Array, c: Float64Array, n: number): void;
+export function matrix_multiply_ptr(a_ptr: number, b_ptr: number, c_ptr: number, n: number): void;
+export function matrix_multiply_strassen_ptr(a_ptr: number, b_ptr: number, c_ptr: number, n: number): void;
+export function matrix_multiply_strassen_cancellable_ptr(a_ptr: number, b_ptr: number, c_ptr: number, n: number, control_ptr: number): KernelStatus;
 export function set_strassen_threshold(value: number): void;
 export function get_strassen_threshold(): number;
 export function quicksort(arr: Float64Array): void;
+export function quicksort_ptr(ptr: number, len: number): void;
+export function quicksort_cancellable_ptr(ptr: number, len: number, control_ptr: number): KernelStatus;
+export function thread_count(): number;
 `;
 
   writeFileSync(jsPath, jsStub, 'utf8');
@@ -152,8 +901,13 @@ if (!hasCargo || !hasWasmBindgen) {
   process.exit(0);
 }
 
+const useParallel = process.env.WASM_PARALLEL === '1' || process.env.WASM_PARALLEL === 'true';
 const useShared = process.env.WASM_SHARED === '1' || process.env.WASM_SHARED === 'true';
-const buildScript = useShared ? 'build:wasm:shared' : 'build:wasm';
+const buildScript = useParallel
+  ? 'build:wasm:parallel'
+  : useShared
+    ? 'build:wasm:shared'
+    : 'build:wasm';
 const result = spawnSync('npm', ['run', buildScript], { stdio: 'inherit', shell: true });
 
 if (result.status !== 0) {
@@ -1,6 +1,6 @@
 /// <reference lib="webworker" />
-import init from '../../.wasm/pkg/wasm_lib';
-import type { WasmExports } from './wasm/wasm-utils';
+import init, * as wasmPkg from '../../.wasm/pkg/wasm_lib';
+import { describeWasmError, type WasmExports } from './wasm/wasm-utils';
 import { handleBenchMessage } from './wasm/handlers/bench';
 import { handleSharedMessage } from './wasm/handlers/shared';
 import { handleOpsMessage } from './wasm/handlers/ops';
@@ -29,9 +29,21 @@ const signalComplete = (control: Int32Array) => {
   Atomics.notify(control, 0, 1);
 };
 
+// Only present when built with `npm run build:wasm:parallel`.
+const initThreadPoolIfAvailable = async () => {
+  const { initThreadPool } = wasmPkg as {
+    initThreadPool?: (threads: number) => Promise<void>;
+  };
+  if (typeof initThreadPool !== 'function' || !self.crossOriginIsolated) {
+    return;
+  }
+  await initThreadPool(navigator.hardwareConcurrency || 4);
+};
+
 const ensureWasm = async (): Promise<WasmExports> => {
   if (!isInitialized) {
     const exports = await init();
+    await initThreadPoolIfAvailable();
     wasmExports = exports as unknown as WasmExports;
     isInitialized = true;
   }
@@ -97,12 +109,24 @@ self.onmessage = async (e: MessageEvent<WorkerRequest>) => {
       message: `Unhandled message type: ${message.type}`,
     });
   } catch (error) {
-    const messageText = error instanceof Error ? error.message : 'Unknown worker error';
+    // A failed kernel never reaches its own signalComplete; release any waiter.
+    if (
+      'control' in message &&
+      typeof SharedArrayBuffer !== 'undefined' &&
+      message.control instanceof SharedArrayBuffer
+    ) {
+      signalComplete(new Int32Array(message.control));
+    }
+    const { code, message: messageText } =
+      error instanceof Error
+        ? describeWasmError(error)
+        : { code: undefined, message: 'Unknown worker error' };
     postMessageSafe({
       type: 'error',
       requestId: message.requestId,
       version: WORKER_PROTOCOL_VERSION,
       message: messageText,
+      code,
     });
   }
 };
@@ -2,9 +2,12 @@ import {
   fibonacci,
   fibonacci_iter,
   matrix_multiply,
+  matrix_multiply_ptr,
   matrix_multiply_strassen,
+  matrix_multiply_strassen_ptr,
   process_shared_buffer,
   quicksort,
+  quicksort_ptr,
 } from '../../../../.wasm/pkg/wasm_lib';
 import {
   MAX_BUFFER_LENGTH,
@@ -82,8 +85,8 @@ export const handleBenchMessage = async (
             aPtr.view[i] = ptrRng();
             bPtr.view[i] = ptrRng();
           }
-          wasm.matrix_multiply_ptr(aPtr.ptr, bPtr.ptr, cPtr.ptr, size);
-          wasm.quicksort_ptr(cPtr.ptr, count);
+          matrix_multiply_ptr(aPtr.ptr, bPtr.ptr, cPtr.ptr, size);
+          quicksort_ptr(cPtr.ptr, count);
         } finally {
           if (aPtr) wasm.free_f64(aPtr.ptr, count);
           if (bPtr) wasm.free_f64(bPtr.ptr, count);
@@ -295,9 +298,9 @@ export const handleBenchMessage = async (
             : 'naive';
         const start = performance.now();
         if (algorithmUsed === 'strassen') {
-          wasm.matrix_multiply_strassen_ptr(a.ptr, b.ptr, c.ptr, n);
+          matrix_multiply_strassen_ptr(a.ptr, b.ptr, c.ptr, n);
         } else {
-          wasm.matrix_multiply_ptr(a.ptr, b.ptr, c.ptr, n);
+          matrix_multiply_ptr(a.ptr, b.ptr, c.ptr, n);
         }
         const durationMs = performance.now() - start;
         deps.postMessageSafe({
@@ -388,7 +391,7 @@ export const handleBenchMessage = async (
           buffer.view[i] = rng();
         }
         const start = performance.now();
-        wasm.quicksort_ptr(buffer.ptr, message.length);
+        quicksort_ptr(buffer.ptr, message.length);
         const durationMs = performance.now() - start;
         deps.postMessageSafe({
           type: 'quicksortWasmBenchDone',
@@ -1,4 +1,7 @@
-import { process_shared_buffer } from '../../../../.wasm/pkg/wasm_lib';
+import {
+  process_shared_buffer,
+  process_shared_buffer_ptr,
+} from '../../../../.wasm/pkg/wasm_lib';
 import {
   MAX_BUFFER_LENGTH,
   WORKER_PROTOCOL_VERSION,
@@ -76,7 +79,7 @@ export const handleSharedMessage = async (
       const wasm = await deps.ensureWasm();
       const control = new Int32Array(message.control);
       const start = performance.now();
-      wasm.process_shared_buffer_ptr(message.ptr, message.length);
+      process_shared_buffer_ptr(message.ptr, message.length);
       const durationMs = performance.now() - start;
       deps.signalComplete(control);
       deps.postMessageSafe({
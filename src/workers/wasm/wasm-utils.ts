@@ -1,5 +1,7 @@
 import {
+  ErrorCode,
   get_strassen_threshold,
+  matrix_multiply_strassen_ptr,
   set_strassen_threshold,
 } from '../../../.wasm/pkg/wasm_lib';
 
@@ -9,15 +11,29 @@ export type WasmExports = {
   free_f64: (ptr: number, len: number) => void;
   alloc_u32: (len: number) => number;
   free_u32: (ptr: number, len: number) => void;
-  process_shared_buffer_ptr: (ptr: number, len: number) => void;
-  matrix_multiply_ptr: (aPtr: number, bPtr: number, cPtr: number, n: number) => void;
-  matrix_multiply_strassen_ptr: (aPtr: number, bPtr: number, cPtr: number, n: number) => void;
-  quicksort_ptr: (ptr: number, len: number) => void;
+  alloc_control: () => number;
+  free_control: (ptr: number) => void;
 };
 
+/** Word offsets inside a control block from `alloc_control`. */
+export const CONTROL_CANCEL = 0;
+export const CONTROL_PROGRESS = 1;
+export const CONTROL_TOTAL = 2;
+
 const STRASSEN_DEFAULT_THRESHOLD = 128;
 let strassenThresholdTuned = false;
 
+/**
+ * Fallible wasm exports throw an `Error` whose message starts with an
+ * `ErrorCode` variant name ("BufferTooSmall: ..."). Recovers that code.
+ */
+export const describeWasmError = (error: unknown): { code?: ErrorCode; message: string } => {
+  const message = error instanceof Error ? error.message : String(error);
+  const name = message.split(':', 1)[0];
+  const code = (ErrorCode as unknown as Record<string, unknown>)[name];
+  return typeof code === 'number' ? { code: code as ErrorCode, message } : { message };
+};
+
 export const makeRng = (seed: number) => {
   let state = seed >>> 0;
   return () => {
@@ -69,7 +85,7 @@ export const tuneStrassenThreshold = async (wasm: WasmExports) => {
     for (const threshold of candidates) {
       set_strassen_threshold(threshold);
       const start = performance.now();
-      wasm.matrix_multiply_strassen_ptr(a.ptr, b.ptr, c.ptr, n);
+      matrix_multiply_strassen_ptr(a.ptr, b.ptr, c.ptr, n);
       const durationMs = performance.now() - start;
       if (durationMs < bestTime) {
         bestTime = durationMs;
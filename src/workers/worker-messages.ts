@@ -52,7 +52,7 @@ export type WorkerResponse =
   | { type: 'warmup'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION }
   | { type: 'ready'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION }
   | { type: 'warmupDone'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION }
-  | { type: 'error'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION; message: string }
+  | { type: 'error'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION; message: string; code?: number }
   // Fibonacci results
   | { type: 'fibonacciResult'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION; result: number }
   | { type: 'fibonacciIterResult'; requestId: string; version: typeof WORKER_PROTOCOL_VERSION; result: bigint }
@@ -44,7 +44,7 @@ export class WorkerPool {
         clearTimeout(handler.timeoutId);
 
         if (message.type === 'error') {
-          handler.reject(new Error(message.message));
+          handler.reject(Object.assign(new Error(message.message), { code: message.code }));
         } else {
           handler.resolve(message);
         }
@@ -6,6 +6,20 @@ const wasmPath = join(process.cwd(), '.wasm', 'pkg', 'wasm_lib_bg.wasm');
 const modulePath = '../../.wasm/pkg/wasm_lib.js';
 const testIf = existsSync(wasmPath) ? test : test.skip;
 
+/** O(n^2) reference DFT of `[re, im]` pairs. */
+function directDft(input: number[][]): number[][] {
+  return input.map((_, k) => {
+    let re = 0;
+    let im = 0;
+    input.forEach(([xr, xi], t) => {
+      const angle = (-2 * Math.PI * ((k * t) % input.length)) / input.length;
+      re += xr * Math.cos(angle) - xi * Math.sin(angle);
+      im += xr * Math.sin(angle) + xi * Math.cos(angle);
+    });
+    return [re, im];
+  });
+}
+
 describe('wasm_lib golden values', () => {
   testIf('fibonacci recursive - computes correct values', async () => {
     const wasmBytes = readFileSync(wasmPath);
@@ -79,4 +93,1520 @@ describe('wasm_lib golden values', () => {
     wasmModule.quicksort(arr);
     expect(Array.from(arr)).toEqual([1, 2, 5, 8, 9]);
   });
+
+  testIf('box_blur - separable running sums match the clipped direct average', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    const directBlur = (source: Uint8Array, width: number, height: number, radius: number) => {
+      const out = source.slice();
+      for (let y = 0; y < height; y++) {
+        for (let x = 0; x < width; x++) {
+          const sums = [0, 0, 0];
+          let count = 0;
+          for (let ny = Math.max(0, y - radius); ny <= Math.min(height - 1, y + radius); ny++) {
+            for (let nx = Math.max(0, x - radius); nx <= Math.min(width - 1, x + radius); nx++) {
+              for (let c = 0; c < 3; c++) sums[c] += source[(ny * width + nx) * 4 + c];
+              count++;
+            }
+          }
+          for (let c = 0; c < 3; c++) out[(y * width + x) * 4 + c] = Math.floor(sums[c] / count);
+        }
+      }
+      return out;
+    };
+
+    let seed = 7;
+    for (const [width, height] of [
+      [1, 1],
+      [1, 9],
+      [11, 1],
+      [23, 17],
+    ]) {
+      const image = Uint8Array.from({ length: width * height * 4 }, () => {
+        seed = (seed * 1103515245 + 12345) % 2147483648;
+        return seed % 256;
+      });
+      for (const radius of [0, 1, 4, 30, 0xffffffff]) {
+        const blurred = image.slice();
+        wasmModule.box_blur(blurred, width, height, radius);
+        expect(Array.from(blurred)).toEqual(Array.from(directBlur(image, width, height, radius)));
+      }
+    }
+  });
+
+  testIf('gaussian_blur - sampled and recursive kernels, edge modes', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    const { EdgeMode } = wasmModule;
+    const size = 48;
+    const pixel = (x: number, y: number) => (y * size + x) * 4;
+
+    // A centred dot spreads into a Gaussian whose row profile has the requested sigma.
+    for (const sigma of [1.5, 6]) {
+      const dot = new Uint8Array(size * size * 4);
+      for (let i = 3; i < dot.length; i += 4) dot[i] = 200;
+      for (let y = 20; y < 28; y++) {
+        for (let x = 20; x < 28; x++) dot.set([255, 255, 255], pixel(x, y));
+      }
+      wasmModule.gaussian_blur(dot, size, size, sigma, EdgeMode.Clamp);
+      let mass = 0;
+      let spread = 0;
+      for (let x = 0; x < size; x++) {
+        mass += dot[pixel(x, 24)];
+        spread += dot[pixel(x, 24)] * (x - 23.5) ** 2;
+      }
+      // An 8 px box has variance (8^2 - 1) / 12 before blurring.
+      expect(Math.sqrt(spread / mass - 63 / 12)).toBeCloseTo(sigma, 0);
+      expect(dot[pixel(24, 24) + 3]).toBe(200);
+      expect(dot[pixel(0, 0)]).toBe(0);
+    }
+
+    // Flat images stay flat except with a zero (constant) border.
+    for (const edge of [EdgeMode.Clamp, EdgeMode.Mirror, EdgeMode.Wrap, EdgeMode.Constant]) {
+      const flat = new Uint8Array(16 * 12 * 4).fill(180);
+      wasmModule.gaussian_blur(flat, 16, 12, 4, edge);
+      expect(flat[0] === 180).toBe(edge !== EdgeMode.Constant);
+      expect(flat[3]).toBe(180);
+    }
+
+    // Wrap blurs a vertical edge at the image border; clamp leaves it sharp.
+    const halves = new Uint8Array(size * size * 4);
+    for (let i = 0; i < size * size; i++) {
+      const value = i % size < size / 2 ? 0 : 240;
+      halves.set([value, value, value, 255], i * 4);
+    }
+    const clamped = halves.slice();
+    wasmModule.gaussian_blur(clamped, size, size, 2, EdgeMode.Clamp);
+    const wrapped = halves.slice();
+    wasmModule.gaussian_blur(wrapped, size, size, 2, EdgeMode.Wrap);
+    expect(clamped[pixel(0, 10)]).toBe(0);
+    expect(wrapped[pixel(0, 10)]).toBeGreaterThan(60);
+    expect(wrapped[pixel(24, 10)]).toBe(clamped[pixel(24, 10)]);
+
+    expect(() => wasmModule.gaussian_blur(halves, size, size, 0, EdgeMode.Clamp)).toThrow(
+      /^InvalidArgument:/,
+    );
+    expect(() => wasmModule.gaussian_blur(halves, size, size + 1, 2, EdgeMode.Clamp)).toThrow(
+      /^BufferTooSmall:/,
+    );
+  });
+
+  testIf('convolve_rgba - custom kernels, separable path, divisor, bias and alpha', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    const { EdgeMode } = wasmModule;
+    const width = 9;
+    const height = 7;
+    let seed = 3;
+    const image = Uint8Array.from({ length: width * height * 4 }, () => {
+      seed = (seed * 1103515245 + 12345) % 2147483648;
+      return seed % 256;
+    });
+    const at = (data: Uint8Array, x: number, y: number, c: number) => {
+      const cx = Math.min(width - 1, Math.max(0, x));
+      const cy = Math.min(height - 1, Math.max(0, y));
+      return data[(cy * width + cx) * 4 + c];
+    };
+
+    // Sharpen (not separable) and a 3x3 binomial (separable, auto divisor 16).
+    const sharpen = new Float32Array([0, -1, 0, -1, 5, -1, 0, -1, 0]);
+    const binomial = new Float32Array([1, 2, 1, 2, 4, 2, 1, 2, 1]);
+    for (const [kernel, divisor] of [
+      [sharpen, 1],
+      [binomial, 16],
+    ] as const) {
+      const out = image.slice();
+      wasmModule.convolve_rgba(out, width, height, kernel, 3, 3, 0, 0, EdgeMode.Clamp, false);
+      for (let y = 0; y < height; y++) {
+        for (let x = 0; x < width; x++) {
+          for (let c = 0; c < 3; c++) {
+            let sum = 0;
+            for (let j = 0; j < 3; j++) {
+              for (let i = 0; i < 3; i++) {
+                sum += kernel[j * 3 + i] * at(image, x + i - 1, y + j - 1, c);
+              }
+            }
+            const expected = Math.min(255, Math.max(0, Math.round(sum / divisor)));
+            expect(out[(y * width + x) * 4 + c]).toBe(expected);
+          }
+          expect(out[(y * width + x) * 4 + 3]).toBe(image[(y * width + x) * 4 + 3]);
+        }
+      }
+    }
+
+    // Identity with divisor 2 and bias 100, alpha included.
+    const halved = image.slice();
+    const identity = new Float32Array([1]);
+    wasmModule.convolve_rgba(halved, width, height, identity, 1, 1, 2, 100, EdgeMode.Wrap, true);
+    halved.forEach((value, i) => expect(value).toBe(Math.min(255, Math.round(image[i] / 2 + 100))));
+
+    // A 2x1 kernel anchored at its left tap reads the right neighbour; wrap crosses the edge.
+    const shifted = image.slice();
+    const right = new Float32Array([0, 1]);
+    wasmModule.convolve_rgba(shifted, width, height, right, 2, 1, 1, 0, EdgeMode.Wrap, true);
+    expect(shifted[(width - 1) * 4]).toBe(image[0]);
+    expect(shifted[0]).toBe(image[4]);
+
+    expect(() =>
+      wasmModule.convolve_rgba(image, width, height, sharpen, 3, 2, 1, 0, EdgeMode.Clamp, false),
+    ).toThrow(/^LengthMismatch:/);
+    expect(() =>
+      wasmModule.convolve_rgba(image, width, height, sharpen, 0, 3, 1, 0, EdgeMode.Clamp, false),
+    ).toThrow(/^InvalidDimensions:/);
+  });
+
+  testIf('image_gradient/canny - operators, directions and thin closed edges', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    const { GradientOperator } = wasmModule;
+    const width = 64;
+    const height = 48;
+    const pixels = width * height;
+
+    // Diagonal ramp 2x + 3y: every operator recovers the slope (doubled) and its angle.
+    const ramp = new Uint8Array(pixels * 4);
+    for (let i = 0; i < pixels; i++) {
+      const value = 2 * (i % width) + 3 * Math.floor(i / width);
+      ramp.set([value, value, value, 255], i * 4);
+    }
+    wasmModule.grayscale(ramp);
+    const magnitude = new Float32Array(pixels);
+    const direction = new Float32Array(pixels);
+    const operators = [GradientOperator.Sobel, GradientOperator.Scharr, GradientOperator.Prewitt];
+    for (const operator of operators) {
+      wasmModule.image_gradient(ramp, width, height, operator, magnitude, direction);
+      const centre = 20 * width + 30;
+      expect(magnitude[centre]).toBeCloseTo(2 * Math.hypot(2, 3), 4);
+      expect(direction[centre]).toBeCloseTo(Math.atan2(3, 2), 5);
+    }
+
+    // A black-to-white vertical step saturates the u8 map on both sides of the edge.
+    const step = new Uint8Array(pixels * 4);
+    for (let i = 0; i < pixels; i++) {
+      const value = i % width < 32 ? 0 : 255;
+      step.set([value, value, value, 255], i * 4);
+    }
+    const map = new Uint8Array(pixels);
+    wasmModule.gradient_magnitude(step, width, height, GradientOperator.Sobel, map);
+    expect(Array.from(map.subarray(10 * width + 29, 10 * width + 35))).toEqual([
+      0, 0, 255, 255, 0, 0,
+    ]);
+
+    // Canny outlines a filled rectangle with a one-pixel-wide closed contour.
+    const box = new Uint8Array(pixels * 4);
+    for (let i = 0; i < pixels; i++) {
+      const x = i % width;
+      const y = Math.floor(i / width);
+      const inside = x >= 16 && x < 48 && y >= 12 && y < 36;
+      box.set(inside ? [200, 200, 200, 255] : [40, 40, 40, 255], i * 4);
+    }
+    const edges = new Uint8Array(pixels);
+    const count = wasmModule.canny(box, width, height, 1, 20, 60, GradientOperator.Sobel, edges);
+    expect(count).toBe(edges.filter((value) => value === 255).length);
+    expect(Math.abs(count - 2 * (32 + 24))).toBeLessThan(12);
+    for (const y of [20, 24, 28]) {
+      const row = Array.from(edges.subarray(y * width, (y + 1) * width));
+      expect(row.filter((value) => value === 255).length).toBe(2);
+    }
+    expect(edges.every((value) => value === 0 || value === 255)).toBe(true);
+
+    expect(
+      wasmModule.canny(box, width, height, 1, 20, 60000, GradientOperator.Sobel, edges),
+    ).toBe(0);
+    expect(() =>
+      wasmModule.canny(box, width, height, 1, 60, 20, GradientOperator.Sobel, edges),
+    ).toThrow(/^InvalidArgument:/);
+    expect(() =>
+      wasmModule.gradient_magnitude(box, width, height, GradientOperator.Sobel, map.subarray(1)),
+    ).toThrow(/^BufferTooSmall:/);
+  });
+
+  testIf('invalid inputs - throw errors tagged with an ErrorCode', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    const { ErrorCode } = wasmModule;
+    expect(() => wasmModule.box_blur(new Uint8Array(12), 2, 2, 1)).toThrow(/^BufferTooSmall:/);
+    expect(() => wasmModule.box_blur(new Uint8Array(16), 0, 2, 1)).toThrow(/^InvalidDimensions:/);
+    expect(() => wasmModule.grayscale(new Uint8Array(5))).toThrow(/^InvalidDimensions:/);
+    expect(() =>
+      wasmModule.dot_product_simd(new Float32Array(3), new Float32Array(4))
+    ).toThrow(/^LengthMismatch:/);
+    expect(() => wasmModule.fft_demo(new Float64Array(8), new Float64Array(4))).toThrow(
+      /^BufferTooSmall:/
+    );
+    expect(() => wasmModule.generate_signal(new Float64Array(8), NaN, 1, 1)).toThrow(
+      /^InvalidArgument:/
+    );
+    expect(() => wasmModule.quicksort_ptr(0, 4)).toThrow(/^NullPointer:/);
+    expect(ErrorCode.NullPointer).toBe(1);
+  });
+
+  testIf('matrix entry points - reject short, empty and overflowing inputs', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    const wasm = wasmModule.initSync(wasmBytes);
+
+    const full = new Float64Array(4);
+    const short = new Float64Array(3);
+    const empty = new Float64Array(0);
+    for (const multiply of [wasmModule.matrix_multiply, wasmModule.matrix_multiply_strassen]) {
+      expect(() => multiply(short, full, new Float64Array(4), 2)).toThrow(/^BufferTooSmall:/);
+      expect(() => multiply(full, short, new Float64Array(4), 2)).toThrow(/^BufferTooSmall:/);
+      expect(() => multiply(full, full, short, 2)).toThrow(/^BufferTooSmall:/);
+      expect(() => multiply(empty, empty, empty, 2)).toThrow(/^BufferTooSmall:/);
+      expect(() => multiply(empty, empty, empty, 0)).not.toThrow();
+      // 65536 * 65536 does not fit in a 32-bit usize.
+      expect(() => multiply(empty, empty, empty, 65536)).toThrow(/^SizeOverflow:/);
+    }
+
+    const n = 2;
+    const a = wasm.alloc_f64(n * n);
+    const b = wasm.alloc_f64(n * n);
+    const c = wasm.alloc_f64(n * n);
+    try {
+      new Float64Array(wasm.memory.buffer, a, n * n).set([1, 0, 0, 1]);
+      new Float64Array(wasm.memory.buffer, b, n * n).set([1, 2, 3, 4]);
+      for (const multiplyPtr of [
+        wasmModule.matrix_multiply_ptr,
+        wasmModule.matrix_multiply_strassen_ptr,
+      ]) {
+        expect(() => multiplyPtr(0, 0, 0, 0)).not.toThrow();
+        expect(() => multiplyPtr(0, b, c, n)).toThrow(/^NullPointer:/);
+        expect(() => multiplyPtr(a, b, c, 65536)).toThrow(/^SizeOverflow:/);
+        expect(() => multiplyPtr(a, b, a, n)).toThrow(/^InvalidArgument:/);
+        expect(() => multiplyPtr(a + 4, b, c, n)).toThrow(/^InvalidArgument:/);
+        multiplyPtr(a, b, c, n);
+        expect(Array.from(new Float64Array(wasm.memory.buffer, c, n * n))).toEqual([1, 2, 3, 4]);
+      }
+    } finally {
+      wasm.free_f64(a, n * n);
+      wasm.free_f64(b, n * n);
+      wasm.free_f64(c, n * n);
+    }
+  });
+
+  testIf('fft_complex - radix-2 and split-radix match a direct DFT', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { FftAlgorithm } = wasmModule;
+
+    // Impulse -> flat spectrum.
+    const impulseRe = new Float64Array([1, 0, 0, 0, 0, 0, 0, 0]);
+    const impulseIm = new Float64Array(8);
+    wasmModule.fft_complex(impulseRe, impulseIm, FftAlgorithm.Radix2);
+    expect(Array.from(impulseRe)).toEqual([1, 1, 1, 1, 1, 1, 1, 1]);
+    expect(Array.from(impulseIm)).toEqual([0, 0, 0, 0, 0, 0, 0, 0]);
+
+    // cos(2*pi*3*t/16) -> n/2 at bins 3 and 13.
+    const n = 16;
+    const cosine = Float64Array.from({ length: n }, (_, t) =>
+      Math.cos((2 * Math.PI * 3 * t) / n),
+    );
+    const magnitude = new Float64Array(n);
+    wasmModule.fft_demo(cosine, magnitude);
+    magnitude.forEach((value, k) => {
+      expect(value).toBeCloseTo(k === 3 || k === 13 ? n / 2 : 0, 9);
+    });
+
+    const input = Array.from({ length: 64 }, (_, t) => [
+      Math.sin(t * 0.7) + 0.25,
+      Math.cos(t * 1.3),
+    ]);
+    const expected = directDft(input);
+    for (const algorithm of [FftAlgorithm.Radix2, FftAlgorithm.SplitRadix]) {
+      const re = Float64Array.from(input, ([xr]) => xr);
+      const im = Float64Array.from(input, ([, xi]) => xi);
+      wasmModule.fft_complex(re, im, algorithm);
+      const interleaved = Float64Array.from(input.flat());
+      wasmModule.fft_interleaved(interleaved, algorithm);
+      expected.forEach(([er, ei], k) => {
+        expect(re[k]).toBeCloseTo(er, 9);
+        expect(im[k]).toBeCloseTo(ei, 9);
+        expect(interleaved[2 * k]).toBeCloseTo(er, 9);
+        expect(interleaved[2 * k + 1]).toBeCloseTo(ei, 9);
+      });
+    }
+
+    const power = new Float64Array(2);
+    wasmModule.fft_spectrum(
+      new Float64Array([3, 0]),
+      new Float64Array([4, -2]),
+      power,
+      wasmModule.SpectrumKind.Power,
+    );
+    expect(Array.from(power)).toEqual([25, 4]);
+
+    const radix2 = FftAlgorithm.Radix2;
+    expect(() =>
+      wasmModule.fft_complex(new Float64Array(6), new Float64Array(6), radix2),
+    ).toThrow(/^InvalidDimensions:/);
+    expect(() =>
+      wasmModule.fft_complex(new Float64Array(4), new Float64Array(2), radix2),
+    ).toThrow(/^LengthMismatch:/);
+    expect(() => wasmModule.fft_interleaved(new Float64Array(3), radix2)).toThrow(
+      /^InvalidDimensions:/,
+    );
+  });
+
+  testIf('FftPlan - mixed radix and Bluestein lengths match a direct DFT', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    // 60 = 4 * 3 * 5 (mixed radix), 97 is prime and 14 = 2 * 7 (Bluestein).
+    for (const n of [1, 3, 5, 60, 97, 14]) {
+      const input = Array.from({ length: n }, (_, t) => [
+        Math.sin(t * 0.9) - 0.1,
+        Math.cos(t * 0.4),
+      ]);
+      const expected = directDft(input);
+      const plan = new wasmModule.FftPlan(n);
+      try {
+        expect(plan.len).toBe(n);
+        const re = Float64Array.from(input, ([xr]) => xr);
+        const im = Float64Array.from(input, ([, xi]) => xi);
+        plan.forward(re, im);
+        const interleaved = Float64Array.from(input.flat());
+        plan.forward_interleaved(interleaved);
+        expected.forEach(([er, ei], k) => {
+          expect(re[k]).toBeCloseTo(er, 9);
+          expect(im[k]).toBeCloseTo(ei, 9);
+          expect(interleaved[2 * k]).toBeCloseTo(er, 9);
+          expect(interleaved[2 * k + 1]).toBeCloseTo(ei, 9);
+        });
+        expect(() => plan.forward(new Float64Array(n + 1), new Float64Array(n + 1))).toThrow(
+          /^LengthMismatch:/,
+        );
+      } finally {
+        plan.free();
+      }
+    }
+
+    // fft_demo now handles any length with the same planner.
+    const signal = Float64Array.from({ length: 1000 }, (_, t) =>
+      Math.cos((2 * Math.PI * 50 * t) / 1000),
+    );
+    const magnitude = new Float64Array(1000);
+    wasmModule.fft_demo(signal, magnitude);
+    expect(magnitude[50]).toBeCloseTo(500, 9);
+    expect(magnitude[950]).toBeCloseTo(500, 9);
+    expect(magnitude[51]).toBeCloseTo(0, 9);
+  });
+
+  testIf('inverse FFT and rfft/irfft - generate_signal round-trips within 1e-9', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { FftAlgorithm } = wasmModule;
+
+    const maxError = (a: Float64Array, b: Float64Array) =>
+      a.reduce((max, value, i) => Math.max(max, Math.abs(value - b[i])), 0);
+
+    // Power-of-two, mixed-radix, prime and odd lengths.
+    for (const n of [1024, 1000, 997, 45]) {
+      const signal = new Float64Array(n);
+      wasmModule.generate_signal(signal, 5, 12, 31);
+
+      const bins = Math.floor(n / 2) + 1;
+      const spectrumRe = new Float64Array(bins);
+      const spectrumIm = new Float64Array(bins);
+      wasmModule.rfft(signal, spectrumRe, spectrumIm);
+      const restored = new Float64Array(n);
+      wasmModule.irfft(spectrumRe, spectrumIm, restored);
+      expect(maxError(restored, signal)).toBeLessThan(1e-9);
+
+      const interleaved = new Float64Array(2 * bins);
+      wasmModule.rfft_interleaved(signal, interleaved);
+      interleaved.forEach((value, i) => {
+        expect(value).toBeCloseTo(i % 2 === 0 ? spectrumRe[i / 2] : spectrumIm[(i - 1) / 2], 9);
+      });
+      wasmModule.irfft_interleaved(interleaved, restored);
+      expect(maxError(restored, signal)).toBeLessThan(1e-9);
+
+      const plan = new wasmModule.FftPlan(n);
+      try {
+        const re = signal.slice();
+        const im = new Float64Array(n);
+        plan.forward(re, im);
+        expect(maxError(re.subarray(0, bins), spectrumRe)).toBeLessThan(1e-9);
+        expect(maxError(im.subarray(0, bins), spectrumIm)).toBeLessThan(1e-9);
+        plan.inverse(re, im);
+        expect(maxError(re, signal)).toBeLessThan(1e-9);
+        expect(maxError(im, new Float64Array(n))).toBeLessThan(1e-9);
+      } finally {
+        plan.free();
+      }
+    }
+
+    const signal = new Float64Array(256);
+    wasmModule.generate_signal(signal, 3, 7, 40);
+    for (const algorithm of [FftAlgorithm.Radix2, FftAlgorithm.SplitRadix]) {
+      const re = signal.slice();
+      const im = new Float64Array(256);
+      wasmModule.fft_complex(re, im, algorithm);
+      wasmModule.ifft_complex(re, im, algorithm);
+      expect(maxError(re, signal)).toBeLessThan(1e-9);
+      expect(maxError(im, new Float64Array(256))).toBeLessThan(1e-9);
+    }
+
+    expect(() => wasmModule.rfft(signal, new Float64Array(128), new Float64Array(129))).toThrow(
+      /^BufferTooSmall:/,
+    );
+    expect(() =>
+      wasmModule.ifft_complex(new Float64Array(12), new Float64Array(12), FftAlgorithm.Radix2),
+    ).toThrow(/^InvalidDimensions:/);
+  });
+
+  testIf('windows - gains, shapes and leakage on generate_signal', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { WindowKind } = wasmModule;
+
+    const gain = (kind: number, param = 0) =>
+      wasmModule.window_coherent_gain(kind, param, 4096, true);
+    const enbw = (kind: number, param = 0) => wasmModule.window_enbw(kind, param, 4096, true);
+    expect(gain(WindowKind.Rectangular)).toBeCloseTo(1, 12);
+    expect(enbw(WindowKind.Rectangular)).toBeCloseTo(1, 12);
+    expect(gain(WindowKind.Hann)).toBeCloseTo(0.5, 12);
+    expect(enbw(WindowKind.Hann)).toBeCloseTo(1.5, 12);
+    expect(gain(WindowKind.Hamming)).toBeCloseTo(0.54, 12);
+    expect(enbw(WindowKind.Hamming)).toBeCloseTo(1.362826, 6);
+    expect(gain(WindowKind.Blackman)).toBeCloseTo(0.42, 12);
+    expect(enbw(WindowKind.BlackmanHarris)).toBeCloseTo(2.004353, 6);
+    expect(gain(WindowKind.FlatTop)).toBeCloseTo(0.21557895, 8);
+    expect(enbw(WindowKind.FlatTop)).toBeCloseTo(3.770246, 6);
+    expect(gain(WindowKind.Tukey, 0.5)).toBeCloseTo(0.75, 12);
+
+    const hann = new Float64Array(9);
+    wasmModule.generate_window(hann, WindowKind.Hann, 0, false);
+    expect(hann[0]).toBe(0);
+    expect(hann[4]).toBeCloseTo(1, 15);
+    expect(hann[2]).toBeCloseTo(0.5, 15);
+    const tukey = new Float64Array(9);
+    wasmModule.generate_window(tukey, WindowKind.Tukey, 1, false);
+    tukey.forEach((value, i) => expect(value).toBeCloseTo(hann[i], 15));
+    // numpy.kaiser(9, 5)
+    const kaiser = new Float64Array(9);
+    wasmModule.generate_window(kaiser, WindowKind.Kaiser, 5, false);
+    expect(kaiser[0]).toBeCloseTo(0.036710892, 8);
+    expect(kaiser[2]).toBeCloseTo(0.55285177, 8);
+
+    // Tones halfway between bins: worst case for leakage and amplitude error.
+    const n = 1024;
+    const signal = new Float64Array(n);
+    wasmModule.generate_signal(signal, 10.5, 100.5, 300.5);
+    const amplitudes = (kind: number) => {
+      const windowed = signal.slice();
+      wasmModule.apply_window(windowed, kind, 0, true);
+      const bins = n / 2 + 1;
+      const re = new Float64Array(bins);
+      const im = new Float64Array(bins);
+      wasmModule.rfft(windowed, re, im);
+      const scale = 2 / (n * wasmModule.window_coherent_gain(kind, 0, n, true));
+      return Array.from(re, (value, k) => Math.hypot(value, im[k]) * scale);
+    };
+    const rectangular = amplitudes(WindowKind.Rectangular);
+    const hannSpectrum = amplitudes(WindowKind.Hann);
+    expect(hannSpectrum[200]).toBeLessThan(rectangular[200] / 100);
+
+    const flatTop = amplitudes(WindowKind.FlatTop);
+    const peak = (spectrum: number[], center: number) =>
+      Math.max(...spectrum.slice(center - 3, center + 4));
+    expect(peak(rectangular, 10)).toBeLessThan(0.7);
+    expect(Math.abs(peak(flatTop, 10) - 1)).toBeLessThan(2e-3);
+    expect(Math.abs(peak(flatTop, 100) - 0.5)).toBeLessThan(2e-3);
+    expect(Math.abs(peak(flatTop, 300) - 0.3)).toBeLessThan(2e-3);
+
+    expect(() => wasmModule.generate_window(hann, WindowKind.Kaiser, -1, true)).toThrow(
+      /^InvalidArgument:/,
+    );
+    expect(() => wasmModule.generate_window(hann, WindowKind.Kaiser, 800, true)).toThrow(
+      /^InvalidArgument:/,
+    );
+    // The largest accepted beta still gives a finite, unit-peak window.
+    const narrow = new Float64Array(9);
+    wasmModule.generate_window(narrow, WindowKind.Kaiser, 700, false);
+    expect(narrow.every(Number.isFinite)).toBe(true);
+    expect(narrow[4]).toBeCloseTo(1, 12);
+    expect(() => wasmModule.generate_window(hann, WindowKind.Tukey, 1.5, true)).toThrow(
+      /^InvalidArgument:/,
+    );
+    expect(() => wasmModule.window_enbw(WindowKind.Hann, 0, 0, true)).toThrow(
+      /^InvalidDimensions:/,
+    );
+  });
+
+  testIf('stft/istft - spectrogram layout and overlap-add reconstruction', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { SpectrogramScale, WindowKind } = wasmModule;
+
+    const n = 4096;
+    const frameSize = 512;
+    const hop = 128;
+    const bins = frameSize / 2 + 1;
+    const signal = new Float64Array(n);
+    wasmModule.generate_signal(signal, 64, 333.3, 1200.7);
+    const frames = wasmModule.stft_frame_count(n, frameSize, hop);
+    expect(frames).toBe(29);
+    expect(wasmModule.stft_frame_count(100, frameSize, hop)).toBe(0);
+
+    const re = new Float64Array(frames * bins);
+    const im = new Float64Array(frames * bins);
+    expect(wasmModule.stft(signal, re, im, frameSize, hop, WindowKind.Hann, 0)).toBe(frames);
+    const restored = new Float64Array(n);
+    wasmModule.istft(re, im, restored, frameSize, hop, WindowKind.Hann, 0);
+    // Sample 0 sits under the zero of the periodic Hann window and cannot be recovered.
+    for (let i = 1; i < n; i++) {
+      expect(Math.abs(restored[i] - signal[i])).toBeLessThan(1e-9);
+    }
+
+    const linear = new Float64Array(frames * bins);
+    const decibels = new Float64Array(frames * bins);
+    const spectrogram = (input: Float64Array, out: Float64Array, scale: number) =>
+      wasmModule.stft_magnitude(input, out, frameSize, hop, WindowKind.Hann, 0, scale);
+    spectrogram(signal, linear, SpectrogramScale.Linear);
+    spectrogram(signal, decibels, SpectrogramScale.Decibels);
+    linear.forEach((value, i) => {
+      expect(value).toBeCloseTo(Math.hypot(re[i], im[i]), 9);
+      expect(decibels[i]).toBeCloseTo(Math.max(20 * Math.log10(value), -240), 9);
+    });
+    // 64 cycles per 4096 samples is exactly bin 8 of a 512-sample frame.
+    const row = linear.subarray(5 * bins, 6 * bins);
+    expect(row[8]).toBeCloseTo(frameSize / 4, 2);
+
+    const silence = new Float64Array(frameSize);
+    const silentRow = new Float64Array(bins);
+    spectrogram(silence, silentRow, SpectrogramScale.Decibels);
+    expect(silentRow[0]).toBe(-240);
+
+    expect(() => wasmModule.stft_frame_count(n, frameSize, 0)).toThrow(/^InvalidArgument:/);
+    expect(() =>
+      wasmModule.stft(signal, new Float64Array(bins), im, frameSize, hop, WindowKind.Hann, 0),
+    ).toThrow(/^BufferTooSmall:/);
+  });
+
+  testIf('SignalGenerator - waveforms, seeded noise, chirps and DC offset', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { ChirpKind, NoiseColor, Waveform } = wasmModule;
+
+    const generator = new wasmModule.SignalGenerator(8000);
+    try {
+      const buffer = new Float64Array(8);
+      generator.dc_offset = 0.25;
+      generator.add_wave(Waveform.Square, 1000, 1, 0);
+      generator.render(buffer);
+      expect(Array.from(buffer)).toEqual([1.25, 1.25, 1.25, 1.25, -0.75, -0.75, -0.75, -0.75]);
+
+      generator.clear();
+      generator.dc_offset = 0;
+      generator.add_wave(Waveform.Triangle, 1000, 2, 0);
+      generator.render(buffer);
+      [0, 1, 2, 1, 0, -1, -2, -1].forEach((v, i) => expect(buffer[i]).toBeCloseTo(v, 12));
+
+      generator.clear();
+      generator.add_wave(Waveform.Sawtooth, 1000, 1, 0);
+      generator.render(buffer);
+      [0, 0.25, 0.5, 0.75, -1, -0.75, -0.5, -0.25].forEach((v, i) =>
+        expect(buffer[i]).toBeCloseTo(v, 12),
+      );
+
+      generator.clear();
+      generator.add_pulse(1000, 1, 0, 0.25);
+      generator.add_sine(2000, 0.5, Math.PI / 2);
+      expect(generator.component_count).toBe(2);
+      generator.render(buffer);
+      [1.5, 1, -1.5, -1, -0.5, -1, -1.5, -1].forEach((v, i) =>
+        expect(buffer[i]).toBeCloseTo(v, 12),
+      );
+    } finally {
+      generator.free();
+    }
+
+    // Same seed, same noise; RMS equals the requested amplitude.
+    const noise = (color: number, seed: number) => {
+      const g = new wasmModule.SignalGenerator(48000);
+      try {
+        g.add_noise(color, 0.5, seed);
+        const out = new Float64Array(4096);
+        g.render(out);
+        return out;
+      } finally {
+        g.free();
+      }
+    };
+    for (const color of [NoiseColor.White, NoiseColor.Pink, NoiseColor.Brown]) {
+      const a = noise(color, 7);
+      expect(Array.from(noise(color, 7))).toEqual(Array.from(a));
+      expect(noise(color, 8)[10]).not.toBe(a[10]);
+      const rms = Math.sqrt(a.reduce((sum, v) => sum + v * v, 0) / a.length);
+      expect(rms).toBeCloseTo(0.5, 12);
+    }
+
+    // A 100 -> 1600 Hz log sweep over one second crosses zero ~11 times in its
+    // first 100 ms and ~139 times in its last.
+    const chirp = new wasmModule.SignalGenerator(8000);
+    try {
+      chirp.add_chirp(ChirpKind.Logarithmic, 100, 1600, 1, 1, 0);
+      const sweep = new Float64Array(8000);
+      chirp.render(sweep);
+      const crossings = (from: number, to: number) => {
+        let count = 0;
+        for (let i = from; i < to; i++) if (sweep[i - 1] < 0 && sweep[i] >= 0) count++;
+        return count;
+      };
+      expect(crossings(1, 800)).toBe(11);
+      expect(crossings(7200, 8000)).toBe(139);
+      expect(() => chirp.add_chirp(ChirpKind.Logarithmic, 0, 1600, 1, 1, 0)).toThrow(
+        /^InvalidArgument:/,
+      );
+      expect(() => chirp.add_pulse(100, 1, 0, 1)).toThrow(/^InvalidArgument:/);
+    } finally {
+      chirp.free();
+    }
+    expect(() => new wasmModule.SignalGenerator(0)).toThrow(/^InvalidArgument:/);
+  });
+
+  testIf('FIR design - windowed-sinc and equiripple responses, streaming applier', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { FilterBand, WindowKind } = wasmModule;
+
+    const response = (taps: Float64Array, frequencies: number[]) => {
+      const out = new Float64Array(frequencies.length);
+      wasmModule.fir_magnitude_response(taps, 1000, Float64Array.from(frequencies), out);
+      return Array.from(out);
+    };
+    const probes = [0, 100, 225, 400, 500];
+    const expected = {
+      [FilterBand.Lowpass]: [1, 1, 0, 0, 0],
+      [FilterBand.Highpass]: [0, 0, 1, 1, 1],
+      [FilterBand.Bandpass]: [0, 0, 1, 0, 0],
+      [FilterBand.Bandstop]: [1, 1, 0, 1, 1],
+    };
+    for (const band of [
+      FilterBand.Lowpass,
+      FilterBand.Highpass,
+      FilterBand.Bandpass,
+      FilterBand.Bandstop,
+    ]) {
+      // Lowpass and highpass cut at 160 Hz; the band filters span 150-300 Hz.
+      const f1 = band === FilterBand.Lowpass || band === FilterBand.Highpass ? 160 : 150;
+      const windowed = new Float64Array(61);
+      wasmModule.fir_design_windowed(windowed, band, 1000, f1, 300, WindowKind.Blackman, 0);
+      const equiripple = new Float64Array(61);
+      wasmModule.fir_design_equiripple(equiripple, band, 1000, f1, 300, 40, 1);
+      const wanted = expected[band];
+      response(windowed, probes).forEach((value, i) => {
+        expect(Math.abs(value - wanted[i])).toBeLessThan(1e-3);
+      });
+      response(equiripple, probes).forEach((value, i) => {
+        expect(Math.abs(value - wanted[i])).toBeLessThan(1e-2);
+      });
+      // Linear phase: symmetric taps.
+      for (let i = 0; i < 30; i++) {
+        expect(windowed[i]).toBeCloseTo(windowed[60 - i], 12);
+        expect(equiripple[i]).toBeCloseTo(equiripple[60 - i], 12);
+      }
+    }
+
+    // Equiripple: equal pass/stop ripple, well below a Hamming design of the same length.
+    const grid = (from: number, to: number) =>
+      Array.from({ length: 201 }, (_, i) => from + ((to - from) * i) / 200);
+    const ripple = (taps: Float64Array) => [
+      Math.max(...response(taps, grid(0, 200)).map((v) => Math.abs(v - 1))),
+      Math.max(...response(taps, grid(250, 500))),
+    ];
+    const remez = new Float64Array(41);
+    wasmModule.fir_design_equiripple(remez, FilterBand.Lowpass, 1000, 225, 0, 50, 1);
+    const hamming = new Float64Array(41);
+    const lowpass = FilterBand.Lowpass;
+    wasmModule.fir_design_windowed(hamming, lowpass, 1000, 225, 0, WindowKind.Hamming, 0);
+    const [pass, stop] = ripple(remez);
+    expect(Math.abs(pass - stop) / stop).toBeLessThan(0.05);
+    expect(stop).toBeLessThan(0.011);
+    expect(stop).toBeLessThan(ripple(hamming)[1] / 5);
+
+    // Streaming in 128-sample blocks matches filtering the whole buffer.
+    const taps = new Float64Array(63);
+    wasmModule.fir_design_windowed(taps, FilterBand.Lowpass, 48000, 2000, 0, WindowKind.Hann, 0);
+    const input = new Float64Array(1000);
+    wasmModule.generate_signal(input, 3, 90, 400);
+    const batch = new Float64Array(1000);
+    wasmModule.fir_filter(input, taps, batch);
+    const filter = new wasmModule.FirFilter(taps);
+    try {
+      expect(filter.taps_len).toBe(63);
+      expect(filter.group_delay).toBe(31);
+      const samples = Float32Array.from(input);
+      for (let start = 0; start < samples.length; start += 128) {
+        filter.process_in_place(samples.subarray(start, start + 128));
+      }
+      samples.forEach((value, i) => expect(Math.abs(value - batch[i])).toBeLessThan(1e-5));
+    } finally {
+      filter.free();
+    }
+
+    expect(() =>
+      wasmModule.fir_design_windowed(new Float64Array(60), FilterBand.Highpass, 1000, 160, 0, 0, 0),
+    ).toThrow(/^InvalidDimensions:/);
+    expect(() =>
+      wasmModule.fir_design_equiripple(remez, FilterBand.Bandpass, 1000, 300, 150, 40, 1),
+    ).toThrow(/^InvalidArgument:/);
+  });
+
+  testIf('BiquadCascade - RBJ sections, prototypes and stateful processing', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { BiquadCascade, BiquadKind, FilterBand, IirPrototype } = wasmModule;
+
+    const sampleRate = 48000;
+    const decibels = (cascade: InstanceType<typeof BiquadCascade>, frequencies: number[]) => {
+      const magnitude = new Float64Array(frequencies.length);
+      const phase = new Float64Array(frequencies.length);
+      cascade.frequency_response(sampleRate, Float64Array.from(frequencies), magnitude, phase);
+      return Array.from(magnitude, (m) => 20 * Math.log10(m));
+    };
+
+    const rbj = (kind: number, gainDb: number) => {
+      const cascade = new BiquadCascade();
+      cascade.add_rbj(kind, sampleRate, 1000, Math.SQRT1_2, gainDb);
+      const response = decibels(cascade, [10, 1000, 23999]);
+      cascade.free();
+      return response;
+    };
+    const [lowDc, lowCutoff] = rbj(BiquadKind.Lowpass, 0);
+    expect(lowDc).toBeCloseTo(0, 3);
+    expect(lowCutoff).toBeCloseTo(-3.0103, 3);
+    expect(rbj(BiquadKind.Notch, 0)[1]).toBeLessThan(-100);
+    expect(rbj(BiquadKind.Peaking, 6)[1]).toBeCloseTo(6, 9);
+    expect(rbj(BiquadKind.LowShelf, 6)[0]).toBeCloseTo(6, 3);
+    expect(rbj(BiquadKind.HighShelf, -6)[2]).toBeCloseTo(-6, 3);
+
+    const butterworth = BiquadCascade.design(
+      IirPrototype.Butterworth,
+      FilterBand.Lowpass,
+      5,
+      sampleRate,
+      1000,
+      0,
+    );
+    const chebyshev = BiquadCascade.design(
+      IirPrototype.Chebyshev1,
+      FilterBand.Highpass,
+      4,
+      sampleRate,
+      1000,
+      1,
+    );
+    try {
+      expect(butterworth.section_count).toBe(3);
+      const [dc, cutoff, octaveUp] = decibels(butterworth, [1, 1000, 2000]);
+      expect(dc).toBeCloseTo(0, 6);
+      expect(cutoff).toBeCloseTo(-3.0103, 3);
+      // 5th order: 30 dB per octave.
+      expect(octaveUp).toBeLessThan(-30);
+
+      const passband = Array.from({ length: 50 }, (_, i) => 1000 + i * 400);
+      for (const value of decibels(chebyshev, passband)) {
+        expect(value).toBeLessThan(1e-9);
+        expect(value).toBeGreaterThan(-1 - 1e-9);
+      }
+      expect(decibels(chebyshev, [1000])[0]).toBeCloseTo(-1, 6);
+
+      // State carries over between calls: chunked == whole.
+      const input = new Float64Array(1024);
+      wasmModule.generate_signal(input, 7, 90, 300);
+      const whole = input.slice();
+      butterworth.process(whole);
+      butterworth.reset();
+      const chunked = input.slice();
+      for (let start = 0; start < 1024; start += 100) {
+        butterworth.process(chunked.subarray(start, start + 100));
+      }
+      chunked.forEach((value, i) => expect(value).toBe(whole[i]));
+
+      butterworth.reset();
+      const single = Float32Array.from(input);
+      butterworth.process_f32(single);
+      single.forEach((value, i) => expect(Math.abs(value - whole[i])).toBeLessThan(1e-6));
+    } finally {
+      butterworth.free();
+      chebyshev.free();
+    }
+
+    expect(() =>
+      BiquadCascade.design(IirPrototype.Butterworth, FilterBand.Bandpass, 4, sampleRate, 1000, 0),
+    ).toThrow(/^InvalidArgument:/);
+    expect(() =>
+      BiquadCascade.design(IirPrototype.Butterworth, FilterBand.Lowpass, 4, sampleRate, 30000, 0),
+    ).toThrow(/^InvalidArgument:/);
+  });
+
+  testIf('convolve/correlate - direct, FFT and overlap-add paths, delay estimation', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    let seed = 12345;
+    const random = () => {
+      seed = (seed * 1103515245 + 12345) % 2147483648;
+      return seed / 2147483648 - 0.5;
+    };
+    const randomArray = (length: number) => Float64Array.from({ length }, random);
+    const direct = (a: Float64Array, b: Float64Array) => {
+      const out = new Float64Array(a.length + b.length - 1);
+      a.forEach((x, i) => b.forEach((y, j) => (out[i + j] += x * y)));
+      return out;
+    };
+
+    // Direct (short kernel), single FFT and overlap-add.
+    for (const [lenA, lenB] of [
+      [50, 7],
+      [300, 200],
+      [4000, 150],
+      [150, 4000],
+    ]) {
+      const a = randomArray(lenA);
+      const b = randomArray(lenB);
+      const expected = direct(a, b);
+      const out = new Float64Array(expected.length);
+      wasmModule.convolve(a, b, out);
+      out.forEach((value, i) => expect(Math.abs(value - expected[i])).toBeLessThan(1e-10));
+    }
+
+    const correlation = new Float64Array(5);
+    wasmModule.correlate(
+      Float64Array.from([1, 2, 3]),
+      Float64Array.from([0, 1, 0.5]),
+      correlation,
+    );
+    expect(Array.from(correlation)).toEqual([0.5, 2, 3.5, 3, 0]);
+
+    const auto = new Float64Array(3);
+    wasmModule.autocorrelate(Float64Array.from([1, 2, 3]), auto);
+    expect(Array.from(auto)).toEqual([14, 8, 3]);
+
+    // Impulse response: convolving with a delayed, scaled impulse shifts the input.
+    const signal = randomArray(2000);
+    const impulse = new Float64Array(100);
+    impulse[37] = 0.5;
+    const echoed = new Float32Array(2099);
+    wasmModule.convolve_f32(Float32Array.from(signal), Float32Array.from(impulse), echoed);
+    signal.forEach((value, i) => expect(echoed[i + 37]).toBeCloseTo(value * 0.5, 6));
+
+    const delayed = Float64Array.from(echoed.subarray(0, 2000));
+    expect(wasmModule.estimate_delay(signal, delayed, 100)).toBeCloseTo(37, 3);
+    expect(wasmModule.estimate_delay(delayed, signal, 100)).toBeCloseTo(-37, 3);
+    // A huge max_lag means no limit rather than wrapping the search window.
+    expect(wasmModule.estimate_delay(signal, delayed, 0xffffffff)).toBeCloseTo(37, 3);
+
+    expect(() =>
+      wasmModule.convolve(new Float64Array(4), new Float64Array(3), new Float64Array(5)),
+    ).toThrow(/^BufferTooSmall:/);
+    expect(() =>
+      wasmModule.estimate_delay(new Float64Array(0), new Float64Array(4), 2),
+    ).toThrow(/^InvalidDimensions:/);
+  });
+
+  testIf('find_peaks/estimate_pitch - generate_signal peaks and fundamentals', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { PitchMethod, SignalGenerator, NoiseColor } = wasmModule;
+
+    // One second at n Hz: integer frequencies land exactly on bins.
+    const n = 1024;
+    const signal = new Float64Array(n);
+    wasmModule.generate_signal(signal, 50, 120, 300);
+    const magnitude = new Float64Array(n);
+    wasmModule.fft_demo(signal, magnitude);
+    const positions = new Float64Array(8);
+    const heights = new Float64Array(8);
+    const count = wasmModule.find_peaks(magnitude.subarray(0, n / 2 + 1), 1, 1, positions, heights);
+    expect(count).toBe(3);
+    expect(Array.from(positions.subarray(0, 3))).toEqual([50, 120, 300]);
+    [512, 256, 153.6].forEach((expected, i) => expect(heights[i]).toBeCloseTo(expected, 9));
+
+    // Plateau at 2-3, prominence 1.5 at 6 and 2.5 at 8.
+    const data = Float64Array.from([0, 1, 3, 3, 1, 0, 2, 0.5, 2.5, 0]);
+    const found = (prominence: number, distance: number) => {
+      const written = wasmModule.find_peaks(data, prominence, distance, positions, heights);
+      return Array.from(positions.subarray(0, written), (value) => Number(value.toFixed(4)));
+    };
+    expect(found(0, 0)).toEqual([2.5, 7.9444, 6.0714]);
+    expect(found(1.8, 0)).toEqual([2.5, 7.9444]);
+    expect(found(0, 3)).toEqual([2.5, 7.9444]);
+    const tallest = new Float64Array(1);
+    expect(wasmModule.find_peaks(data, 0, 0, tallest, new Float64Array(1))).toBe(1);
+    expect(tallest[0]).toBe(2.5);
+
+    const sampleRate = 8000;
+    const render = (add: (generator: InstanceType<typeof SignalGenerator>) => void) => {
+      const generator = new SignalGenerator(sampleRate);
+      try {
+        add(generator);
+        const out = new Float64Array(2048);
+        generator.render(out);
+        return out;
+      } finally {
+        generator.free();
+      }
+    };
+    for (const frequency of [82.4, 220, 261.63, 440, 987]) {
+      const tone = render((generator) => generator.add_sine(frequency, 1, 0));
+      for (const method of [PitchMethod.Yin, PitchMethod.Autocorrelation]) {
+        const pitch = wasmModule.estimate_pitch(tone, sampleRate, 60, 1200, method);
+        expect(Math.abs(pitch / frequency - 1)).toBeLessThan(1e-3);
+      }
+    }
+    const tone440 = () => render((generator) => generator.add_sine(440, 1, 0));
+    const noise = render((generator) => generator.add_noise(NoiseColor.White, 1, 3));
+    expect(wasmModule.estimate_pitch(noise, sampleRate, 60, 1200, PitchMethod.Yin)).toBe(0);
+    expect(
+      wasmModule.estimate_pitch(noise, sampleRate, 60, 1200, PitchMethod.Autocorrelation),
+    ).toBe(0);
+
+    expect(() =>
+      wasmModule.estimate_pitch(new Float64Array(100), sampleRate, 60, 1200, PitchMethod.Yin),
+    ).toThrow(/^BufferTooSmall:/);
+    // A tiny min_freq asks for a lag beyond usize on wasm32; it must error, not trap.
+    const long = new Float64Array(4096);
+    for (const method of [PitchMethod.Yin, PitchMethod.Autocorrelation]) {
+      expect(() => wasmModule.estimate_pitch(long, 48000, 1e-6, 1000, method)).toThrow(
+        /^(SizeOverflow|BufferTooSmall):/,
+      );
+    }
+    const recovered = wasmModule.estimate_pitch(tone440(), sampleRate, 60, 1200, PitchMethod.Yin);
+    expect(recovered).toBeCloseTo(440, 0);
+    expect(() =>
+      wasmModule.find_peaks(data, 0, 0, new Float64Array(2), new Float64Array(3)),
+    ).toThrow(/^LengthMismatch:/);
+  });
+
+  testIf('goertzel/dtmf - bin powers and digit round trips', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { FftAlgorithm, NoiseColor, SignalGenerator, SpectrumKind } = wasmModule;
+
+    // On bin frequencies Goertzel equals the FFT power spectrum.
+    const n = 64;
+    const signal = new Float64Array(n);
+    wasmModule.generate_signal(signal, 5, 12, 20);
+    const re = signal.slice();
+    const im = new Float64Array(n);
+    wasmModule.fft_complex(re, im, FftAlgorithm.Radix2);
+    const power = new Float64Array(n);
+    wasmModule.fft_spectrum(re, im, power, SpectrumKind.Power);
+    const bins = [5, 7, 12, 20];
+    const goertzel = new Float64Array(bins.length);
+    wasmModule.goertzel(signal, n, Float64Array.from(bins), goertzel);
+    bins.forEach((bin, i) => expect(goertzel[i]).toBeCloseTo(power[bin], 9));
+    expect(goertzel[0]).toBeCloseTo(1024, 9);
+
+    const digits = '0123456789ABCD*#1133';
+    for (const sampleRate of [8000, 44100]) {
+      const buffer = new Float64Array(sampleRate * 2);
+      const written = wasmModule.generate_dtmf(buffer, sampleRate, digits, 0.04, 0.04);
+      expect(written).toBe(digits.length * Math.round(0.08 * sampleRate));
+      expect(buffer.subarray(written).every((value) => value === 0)).toBe(true);
+      expect(wasmModule.decode_dtmf(buffer, sampleRate)).toBe(digits);
+
+      const generator = new SignalGenerator(sampleRate);
+      try {
+        generator.add_noise(NoiseColor.White, 0.1, 9);
+        const noise = new Float64Array(buffer.length);
+        generator.render(noise);
+        const noisy = buffer.map((value, i) => value + noise[i]);
+        expect(wasmModule.decode_dtmf(noisy, sampleRate)).toBe(digits);
+        expect(wasmModule.decode_dtmf(noise, sampleRate)).toBe('');
+
+        generator.clear();
+        generator.add_sine(697, 1, 0);
+        generator.render(noise);
+        expect(wasmModule.decode_dtmf(noise, sampleRate)).toBe('');
+      } finally {
+        generator.free();
+      }
+    }
+
+    expect(() => wasmModule.generate_dtmf(new Float64Array(8000), 8000, '12x', 0.05, 0.05)).toThrow(
+      /^InvalidArgument:/,
+    );
+    expect(() => wasmModule.generate_dtmf(new Float64Array(100), 8000, '1', 0.05, 0.05)).toThrow(
+      /^BufferTooSmall:/,
+    );
+    // Zero-length tones and bad sample rates are rejected instead of trapping.
+    const scratch = new Float64Array(8000);
+    for (const [rate, tone, gap] of [
+      [8000, 0, 0],
+      [8000, 0, 0.05],
+      [8000, 0.05, -0.01],
+      [0, 0.1, 0.1],
+      [-8000, 0.1, 0.1],
+      [NaN, 0.1, 0.1],
+    ]) {
+      expect(() => wasmModule.generate_dtmf(scratch, rate, '1', tone, gap)).toThrow(
+        /^InvalidArgument:/,
+      );
+    }
+    expect(() => wasmModule.generate_dtmf(scratch, 8000, '1', 1e300, 0)).toThrow(
+      /^(SizeOverflow|BufferTooSmall):/,
+    );
+    expect(wasmModule.generate_dtmf(scratch, 8000, '', 0.05, 0.05)).toBe(0);
+    expect(() => wasmModule.decode_dtmf(new Float64Array(100), 3000)).toThrow(/^InvalidArgument:/);
+  });
+
+  testIf('resample/Resampler - band-limited rate conversion, one-shot and streaming', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { Resampler } = wasmModule;
+
+    // generate_signal treats the buffer as one second, so 48 kHz resampled to
+    // 16 kHz must match the same tones generated at 16 kHz.
+    const capture = new Float64Array(48000);
+    wasmModule.generate_signal(capture, 440, 1000, 3000);
+    const expected = new Float64Array(16000);
+    wasmModule.generate_signal(expected, 440, 1000, 3000);
+    expect(wasmModule.resampled_len(48000, 48000, 16000)).toBe(16000);
+    // Eleven minutes of 44.1 kHz audio: input * up exceeds 2^32 on wasm32.
+    expect(wasmModule.resampled_len(30_000_000, 44100, 48000)).toBe(32653062);
+    const resampled = new Float64Array(16000);
+    expect(wasmModule.resample(capture, 48000, 16000, resampled)).toBe(16000);
+    for (let i = 2000; i < 14000; i++) {
+      expect(Math.abs(resampled[i] - expected[i])).toBeLessThan(1e-4);
+    }
+
+    // A 9 kHz tone is above the new Nyquist frequency and must not alias.
+    const high = new Float64Array(48000);
+    wasmModule.generate_signal(high, 9000, 0, 0);
+    const aliased = new Float64Array(16000);
+    wasmModule.resample(high, 48000, 16000, aliased);
+    for (let i = 2000; i < 14000; i++) {
+      expect(Math.abs(aliased[i])).toBeLessThan(1e-4);
+    }
+
+    // Streaming in uneven chunks equals one-shot, for rational and arbitrary ratios.
+    for (const [inputRate, outputRate] of [
+      [44100, 48000],
+      [8000, 11025.5],
+    ]) {
+      const input = new Float64Array(4000);
+      wasmModule.generate_signal(input, 300, 700, 1300);
+      const oneShot = new Float64Array(wasmModule.resampled_len(4000, inputRate, outputRate));
+      wasmModule.resample(input, inputRate, outputRate, oneShot);
+
+      const resampler = new Resampler(inputRate, outputRate);
+      try {
+        const streamed = [];
+        for (let start = 0; start < input.length; start += 128) {
+          const chunk = input.subarray(start, start + 128);
+          const out = new Float64Array(resampler.output_len(chunk.length));
+          expect(resampler.process(chunk, out)).toBe(out.length);
+          streamed.push(...out);
+        }
+        const tail = new Float64Array(resampler.flush_len());
+        resampler.flush(tail);
+        streamed.push(...tail);
+        expect(streamed).toEqual(Array.from(oneShot));
+      } finally {
+        resampler.free();
+      }
+    }
+
+    const single = new Float32Array(16000);
+    wasmModule.resample_f32(Float32Array.from(capture), 48000, 16000, single);
+    single.forEach((value, i) => expect(Math.abs(value - resampled[i])).toBeLessThan(1e-6));
+
+    const same = new Float64Array(100);
+    wasmModule.resample(capture.subarray(0, 100), 48000, 48000, same);
+    expect(Array.from(same)).toEqual(Array.from(capture.subarray(0, 100)));
+
+    expect(() => new Resampler(1000, 100000)).toThrow(/^InvalidArgument:/);
+    expect(() => wasmModule.resample(capture, 48000, 16000, new Float64Array(100))).toThrow(
+      /^BufferTooSmall:/,
+    );
+  });
+
+  testIf('decode_wav/encode_wav - handcrafted files, round trips, malformed headers', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { WavFormat } = wasmModule;
+
+    // Little-endian RIFF builder; odd chunks get their pad byte.
+    const ascii = (text: string) => [...text].map((c) => c.charCodeAt(0));
+    const le = (value: number, bytes: number) =>
+      Array.from({ length: bytes }, (_, i) => Math.floor(value / 2 ** (8 * i)) & 0xff);
+    const riff = (chunks: Array<[string, number[]]>) => {
+      const body = chunks.flatMap(([id, data]) => [
+        ...ascii(id),
+        ...le(data.length, 4),
+        ...data,
+        ...(data.length % 2 ? [0] : []),
+      ]);
+      return Uint8Array.from([
+        ...ascii('RIFF'),
+        ...le(body.length + 4, 4),
+        ...ascii('WAVE'),
+        ...body,
+      ]);
+    };
+
+    // WAVE_FORMAT_EXTENSIBLE, mono 24-bit PCM, after an odd-sized LIST chunk.
+    const extensibleFmt = [
+      [0xfffe, 2],
+      [1, 2],
+      [48000, 4],
+      [144000, 4],
+      [3, 2],
+      [24, 2],
+      [22, 2],
+      [24, 2],
+      [4, 4],
+      [1, 2],
+      [0, 14],
+    ].flatMap(([value, bytes]) => le(value, bytes));
+    const pcm24 = riff([
+      ['LIST', [1, 2, 3]],
+      ['fmt ', extensibleFmt],
+      ['data', [0xff, 0xff, 0x7f, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00]],
+    ]);
+    const decoded = wasmModule.decode_wav(pcm24);
+    try {
+      expect(decoded.sample_rate).toBe(48000);
+      expect(decoded.channel_count).toBe(1);
+      expect(decoded.frames).toBe(3);
+      expect(decoded.format).toBe(WavFormat.Pcm24);
+      expect(Array.from(decoded.channel_data(0))).toEqual(
+        Array.from(Float32Array.from([8388607 / 8388608, -1, 1 / 8388608])),
+      );
+      expect(() => decoded.channel_data(1)).toThrow(/^InvalidArgument:/);
+    } finally {
+      decoded.free();
+    }
+
+    // generate_signal -> stereo WAV -> back, in every encoding.
+    const n = 1000;
+    const left = new Float64Array(n);
+    wasmModule.generate_signal(left, 50, 120, 300);
+    const planar = new Float64Array(2 * n);
+    left.forEach((value, i) => {
+      planar[i] = value / 1.8;
+      planar[n + i] = -value / 2;
+    });
+    const steps = [
+      [WavFormat.Pcm8, 1, 1 / 128],
+      [WavFormat.Pcm16, 2, 1 / 32768],
+      [WavFormat.Pcm24, 3, 1 / 8388608],
+      [WavFormat.Pcm32, 4, 1e-7],
+      [WavFormat.Float32, 4, 1e-7],
+      [WavFormat.Float64, 8, 1e-7],
+    ];
+    for (const [format, width, tolerance] of steps) {
+      const bytes = wasmModule.encode_wav(planar, 2, 44100, format);
+      expect(bytes.length).toBe(44 + 2 * n * width);
+      const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+      expect(view.getUint32(24, true)).toBe(44100);
+      expect(view.getUint32(28, true)).toBe(44100 * 2 * width);
+      expect(view.getUint32(40, true)).toBe(2 * n * width);
+
+      const audio = wasmModule.decode_wav(bytes);
+      try {
+        expect(audio.format).toBe(format);
+        expect(audio.frames).toBe(n);
+        const channels = [audio.channel_data(0), audio.channel_data(1)];
+        channels.forEach((channel, c) =>
+          channel.forEach((value, i) =>
+            expect(Math.abs(value - planar[c * n + i])).toBeLessThanOrEqual(tolerance),
+          ),
+        );
+      } finally {
+        audio.free();
+      }
+    }
+
+    const fmt16 = [...le(1, 2), ...le(1, 2), ...le(8000, 4), ...le(16000, 4), ...le(2, 2)];
+    const malformed = [
+      Uint8Array.from([1, 2, 3, 4]),
+      riff([['data', [0, 0]]]),
+      riff([['fmt ', [...fmt16, ...le(12, 2)]], ['data', [0, 0]]]),
+      riff([['fmt ', [...fmt16, ...le(16, 2)]]]),
+      riff([['fmt ', [...fmt16, ...le(16, 2)]], ['data', [0, 0]]]).subarray(0, 44),
+    ];
+    for (const bytes of malformed) {
+      expect(() => wasmModule.decode_wav(bytes)).toThrow(/^InvalidFormat:/);
+    }
+    expect(() => wasmModule.encode_wav(new Float64Array(5), 2, 8000, WavFormat.Pcm16)).toThrow(
+      /^LengthMismatch:/,
+    );
+  });
+
+  testIf('MelExtractor - filterbank, log-mel and MFCC features', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { MelExtractor } = wasmModule;
+
+    const sampleRate = 16000;
+    const frameSize = 512;
+    const bins = frameSize / 2 + 1;
+    const mels = 40;
+    const bank = new Float64Array(mels * bins);
+    wasmModule.mel_filterbank(bank, mels, frameSize, sampleRate, 0, 8000);
+    // Overlapping triangles sum to one between the first and last centres.
+    for (let k = 20; k < 200; k++) {
+      let sum = 0;
+      for (let m = 0; m < mels; m++) sum += bank[m * bins + k];
+      expect(sum).toBeCloseTo(1, 12);
+    }
+
+    const dct = new Float64Array(4);
+    wasmModule.dct2(Float64Array.from([2, 2, 2, 2]), dct);
+    [4, 0, 0, 0].forEach((value, i) => expect(dct[i]).toBeCloseTo(value, 12));
+    const firstTwo = new Float64Array(2);
+    wasmModule.dct2(Float64Array.from([1, 0, 0, -1]), firstTwo);
+    expect(firstTwo[1]).toBeCloseTo(Math.cos(Math.PI / 8) * Math.sqrt(2), 12);
+
+    const extractor = new MelExtractor(sampleRate, frameSize, 160, mels, 0, 8000);
+    try {
+      // One second at 16 kHz with a single 1 kHz tone.
+      const signal = new Float64Array(sampleRate);
+      wasmModule.generate_signal(signal, 1000, 0, 0);
+      const frames = extractor.frame_count(signal.length);
+      expect(frames).toBe(1 + Math.floor((sampleRate - frameSize) / 160));
+
+      const mel = new Float64Array(frames * mels);
+      expect(extractor.mel_spectrogram(signal, mel)).toBe(frames);
+      const row = Array.from(mel.subarray(5 * mels, 6 * mels));
+      // Hann-windowed unit sine: (N/4)^2 + 2 (N/8)^2, all passed by the bank.
+      expect(row.reduce((a, b) => a + b, 0)).toBeCloseTo(24576, 6);
+      expect(row.indexOf(Math.max(...row))).toBe(13);
+
+      const logMel = new Float64Array(frames * mels);
+      extractor.log_mel_spectrogram(signal, logMel);
+      expect(logMel[5 * mels + 13]).toBeCloseTo(10 * Math.log10(row[13]), 9);
+
+      const mfcc = new Float64Array(frames * 13);
+      expect(extractor.mfcc(signal, mfcc, 13)).toBe(frames);
+      const expected = new Float64Array(13);
+      wasmModule.dct2(logMel.subarray(5 * mels, 6 * mels), expected);
+      expected.forEach((value, c) => expect(mfcc[5 * 13 + c]).toBeCloseTo(value, 9));
+
+      extractor.log_mel_spectrogram(new Float64Array(sampleRate), logMel);
+      expect(logMel.every((value) => value === -100)).toBe(true);
+
+      expect(() => extractor.mfcc(signal, mfcc, 41)).toThrow(/^InvalidArgument:/);
+      expect(() => extractor.mfcc(signal, new Float64Array(10), 13)).toThrow(/^BufferTooSmall:/);
+    } finally {
+      extractor.free();
+    }
+    expect(() => new MelExtractor(sampleRate, frameSize, 160, mels, 0, 9000)).toThrow(
+      /^InvalidArgument:/,
+    );
+  });
+
+  testIf('welch_psd/analyze_harmonics - densities, levels and distortion figures', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+    const { NoiseColor, SignalGenerator, WindowKind } = wasmModule;
+
+    const sampleRate = 48000;
+    const render = (add: (generator: InstanceType<typeof SignalGenerator>) => void) => {
+      const generator = new SignalGenerator(sampleRate);
+      try {
+        add(generator);
+        const out = new Float64Array(sampleRate);
+        generator.render(out);
+        return out;
+      } finally {
+        generator.free();
+      }
+    };
+    const binWidth = sampleRate / 1024;
+    const psd = new Float64Array(513);
+
+    // White noise with RMS 0.5 spreads 0.25 evenly up to Nyquist.
+    const noise = render((generator) => generator.add_noise(NoiseColor.White, 0.5, 4));
+    const segments = wasmModule.welch_psd(noise, psd, sampleRate, 1024, 512, WindowKind.Hann, 0);
+    expect(segments).toBe(1 + Math.floor((sampleRate - 1024) / 512));
+    const flat = psd.subarray(10, 500).reduce((a, b) => a + b, 0) / 490;
+    expect(Math.abs(flat / (0.25 / (sampleRate / 2)) - 1)).toBeLessThan(0.02);
+
+    // A unit sine carries a mean power of 1/2.
+    const sine = render((generator) => generator.add_sine(1000, 1, 0));
+    wasmModule.welch_psd(sine, psd, sampleRate, 1024, 512, WindowKind.Hann, 0);
+    expect(psd.reduce((a, b) => a + b, 0) * binWidth).toBeCloseTo(0.5, 6);
+    expect(wasmModule.rms(sine)).toBeCloseTo(Math.SQRT1_2, 12);
+    expect(wasmModule.peak_amplitude(sine)).toBeCloseTo(1, 12);
+    expect(wasmModule.crest_factor(sine)).toBeCloseTo(Math.SQRT2, 12);
+    expect(wasmModule.crest_factor(new Float64Array(8))).toBe(0);
+
+    const distorted = render((generator) => {
+      generator.add_sine(1000, 1, 0);
+      generator.add_sine(2000, 0.01, 0.3);
+      generator.add_sine(3000, 0.001, 0.1);
+      generator.add_noise(NoiseColor.White, 0.001, 7);
+    });
+    for (const fundamental of [1000, 0]) {
+      const analysis = wasmModule.analyze_harmonics(distorted, sampleRate, fundamental, 5);
+      try {
+        expect(analysis.fundamental).toBe(1000);
+        expect(analysis.thd_db).toBeCloseTo(10 * Math.log10(1e-4 + 1e-6), 1);
+        expect(analysis.snr_db).toBeCloseTo(10 * Math.log10(0.5 / 1e-6), 0);
+        expect(analysis.sinad_db).toBeCloseTo(10 * Math.log10(0.5 / (0.5 * 1.01e-4 + 1e-6)), 1);
+      } finally {
+        analysis.free();
+      }
+    }
+
+    expect(() =>
+      wasmModule.welch_psd(sine, psd, sampleRate, 1024, 1024, WindowKind.Hann, 0),
+    ).toThrow(/^InvalidArgument:/);
+    expect(() => wasmModule.analyze_harmonics(sine, sampleRate, 30000, 5)).toThrow(
+      /^InvalidArgument:/,
+    );
+    expect(() => wasmModule.analyze_harmonics(sine, sampleRate, 1000, 1)).toThrow(
+      /^InvalidArgument:/,
+    );
+  });
+
+  testIf('analytic_signal/hilbert_envelope - Hilbert pairs, AM demodulation, onsets', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    // H{cos} = sin for whole periods, at even and odd lengths.
+    for (const n of [64, 101]) {
+      const signal = Float64Array.from({ length: n }, (_, t) =>
+        Math.cos((2 * Math.PI * 5 * t) / n),
+      );
+      const re = new Float64Array(n);
+      const im = new Float64Array(n);
+      wasmModule.analytic_signal(signal, re, im);
+      signal.forEach((value, t) => {
+        expect(re[t]).toBeCloseTo(value, 12);
+        expect(im[t]).toBeCloseTo(Math.sin((2 * Math.PI * 5 * t) / n), 12);
+      });
+    }
+
+    // AM demodulation: 5 Hz envelope on a 400 Hz carrier.
+    const sampleRate = 8000;
+    const am = Float64Array.from({ length: sampleRate }, (_, i) => {
+      const t = i / sampleRate;
+      return (1 + 0.5 * Math.cos(2 * Math.PI * 5 * t)) * Math.cos(2 * Math.PI * 400 * t);
+    });
+    const envelope = new Float64Array(sampleRate);
+    const phase = new Float64Array(sampleRate);
+    wasmModule.hilbert_envelope(am, envelope, phase, true);
+    envelope.forEach((value, i) => {
+      expect(value).toBeCloseTo(1 + 0.5 * Math.cos((2 * Math.PI * 5 * i) / sampleRate), 9);
+    });
+    const lastPhase = (2 * Math.PI * 400 * (sampleRate - 1)) / sampleRate;
+    expect(phase[sampleRate - 1]).toBeCloseTo(lastPhase, 6);
+    wasmModule.hilbert_envelope(am, envelope, phase, false);
+    expect(phase.every((value) => Math.abs(value) <= Math.PI)).toBe(true);
+
+    const frequency = new Float64Array(sampleRate);
+    wasmModule.instantaneous_frequency(am, sampleRate, frequency);
+    frequency.forEach((value) => expect(value).toBeCloseTo(400, 6));
+
+    // Onset: generate_signal tones switched on halfway through the buffer.
+    const tones = new Float64Array(sampleRate);
+    wasmModule.generate_signal(tones, 300, 700, 1100);
+    const onset = Float64Array.from(tones, (value, i) => (i < sampleRate / 2 ? 0 : value));
+    wasmModule.hilbert_envelope(onset, envelope, phase, false);
+    const level = (start: number) =>
+      envelope.subarray(start, start + 500).reduce((a, b) => a + b, 0) / 500;
+    expect(level(1000)).toBeLessThan(0.01);
+    expect(level(5500)).toBeGreaterThan(0.5);
+
+    expect(() => wasmModule.hilbert_envelope(am, new Float64Array(10), phase, false)).toThrow(
+      /^BufferTooSmall:/,
+    );
+  });
+
+  testIf('fft2d/frequency_filter - 2D DFT, fftshift, image spectrum, low/high-pass', async () => {
+    const wasmBytes = readFileSync(wasmPath);
+    const wasmModule = await import(modulePath);
+    wasmModule.initSync(wasmBytes);
+
+    // A single 2D cosine lands in bins (u, v) and (w - u, h - v).
+    const width = 12;
+    const height = 10;
+    const re = Float64Array.from({ length: width * height }, (_, i) =>
+      Math.cos(2 * Math.PI * ((3 * (i % width)) / width + (2 * Math.floor(i / width)) / height)),
+    );
+    const original = re.slice();
+    const im = new Float64Array(width * height);
+    wasmModule.fft2d(re, im, width, height);
+    const half = (width * height) / 2;
+    re.forEach((value, i) => {
+      const expected = i === 2 * width + 3 || i === 8 * width + 9 ? half : 0;
+      expect(value).toBeCloseTo(expected, 9);
+      expect(im[i]).toBeCloseTo(0, 9);
+    });
+    wasmModule.ifft2d(re, im, width, height);
+    re.forEach((value, i) => expect(value).toBeCloseTo(original[i], 12));
+
+    const grid = Float64Array.from({ length: 15 }, (_, i) => i);
+    wasmModule.fftshift2d(grid, 5, 3);
+    expect(grid[1 * 5 + 2]).toBe(0);
+    wasmModule.ifftshift2d(grid, 5, 3);
+    expect(Array.from(grid)).toEqual(Array.from({ length: 15 }, (_, i) => i));
+
+    // Vertical stripes: spectrum energy on the centre row, peak (DC) at the centre.
+    const size = 32;
+    const stripes = new Uint8Array(size * size * 4);
+    for (let i = 0; i < size * size; i++) {
+      const value = Math.floor((i % size) / 4) % 2 === 0 ? 200 : 40;
+      stripes.set([value, value, value, 255], i * 4);
+    }
+    const spectrum = new Uint8Array(stripes.length);
+    wasmModule.image_spectrum(stripes, size, size, spectrum);
+    expect(spectrum[(16 * size + 16) * 4]).toBe(255);
+    expect(spectrum[(16 * size + 20) * 4]).toBeGreaterThan(100);
+    expect(spectrum[(4 * size + 4) * 4]).toBe(0);
+    expect(spectrum[3]).toBe(255);
+
+    // Lowpass smooths the stripes towards their mean, highpass keeps only the edges.
+    const low = stripes.slice();
+    wasmModule.frequency_filter(low, size, size, wasmModule.FilterBand.Lowpass, 0.02);
+    const high = stripes.slice();
+    wasmModule.frequency_filter(high, size, size, wasmModule.FilterBand.Highpass, 0.02);
+    const centre = (16 * size + 10) * 4;
+    expect(Math.abs(low[centre] - 120)).toBeLessThan(20);
+    expect(low[centre + 3]).toBe(255);
+    expect(Math.abs(high[centre] - 128 - (stripes[centre] - low[centre]))).toBeLessThanOrEqual(1);
+
+    const flat = new Uint8Array(16 * 16 * 4).fill(90);
+    wasmModule.frequency_filter(flat, 16, 16, wasmModule.FilterBand.Lowpass, 0.1);
+    expect(flat.every((value) => value === 90)).toBe(true);
+
+    expect(() =>
+      wasmModule.frequency_filter(flat, 16, 16, wasmModule.FilterBand.Bandpass, 0.1),
+    ).toThrow(/^InvalidArgument:/);
+    expect(() => wasmModule.fftshift2d(grid, 0, 3)).toThrow(/^InvalidDimensions:/);
+    expect(() => wasmModule.fft2d(re, im, 7, 7)).toThrow(/^LengthMismatch:/);
+  });
 });